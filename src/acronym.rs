@@ -0,0 +1,343 @@
+//! Reading acronyms and initialisms
+//!
+//! An acronym is either spelled out letter by letter, like "FBI", or read as a word, like
+//! "NASA". Which one is guessed from whether its letters could spell an English word: it needs
+//! a vowel, a consonant cluster at its start and end that English words have, and no long
+//! runs of consonants in between. Three letter acronyms are only read as words when they
+//! are a consonant, a vowel and a consonant, like "GIF". Guesses can be overridden with a list
+//! of acronyms and how to read them.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{Error, Result};
+use crate::g2p::Predictor;
+use crate::lookup::{Lookup, Source};
+use crate::Cmudict;
+
+// consonant clusters that start English words
+const ONSETS: &[&str] = &[
+    "bl", "br", "ch", "cl", "cr", "dr", "dw", "fl", "fr", "gl", "gr", "kl", "kn", "kr", "ph",
+    "pl", "pr", "sc", "sh", "sk", "sl", "sm", "sn", "sp", "st", "sw", "th", "tr", "tw", "wh",
+    "wr", "scr", "shr", "spl", "spr", "str", "thr",
+];
+
+// consonant clusters that end English words
+const CODAS: &[&str] = &[
+    "ch", "ck", "ct", "ds", "ft", "ld", "lf", "lk", "lm", "lp", "ls", "lt", "mp", "ms", "nd",
+    "ng", "nk", "ns", "nt", "ps", "pt", "rb", "rd", "rf", "rk", "rl", "rm", "rn", "rp", "rs",
+    "rt", "sh", "sk", "sp", "st", "th", "ts",
+];
+
+/// How an acronym is read
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Reading {
+    /// Spelled out letter by letter, like "FBI"
+    Letters,
+    /// Read as a word, like "NASA"
+    Word,
+    /// Read with a given pronunciation, like "JPEG" as "J" followed by "peg"
+    Pronunciation(Vec<Symbol>),
+}
+
+/// Decides how acronyms are read, with a list of acronyms whose reading shouldn't be guessed
+///
+/// The list can be read from text with one acronym per line, followed by `letters`, `word`,
+/// or the symbols of its pronunciation. Blank lines and lines starting with `;;` are skipped.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Acronyms, Cmudict, LetterRules, Reading, Source, Stress, Symbol};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let rules = LetterRules::english();
+/// let acronyms: Acronyms = "
+/// ;; our own acronyms
+/// MIT letters
+/// JPEG JH EY1 P EH2 G
+/// ".parse()?;
+///
+/// assert_eq!(acronyms.reading("FBI"), Reading::Letters);
+/// assert_eq!(acronyms.reading("NASA"), Reading::Word);
+/// // without the list, "MIT" would be read like "mitt"
+/// assert_eq!(acronyms.reading("MIT"), Reading::Letters);
+///
+/// let fbi = acronyms.pronounce(&dict, "FBI", &rules).unwrap();
+/// assert_eq!(fbi.source(), Source::Derived);
+/// assert_eq!(
+///     fbi.rules()[0].pronunciation(),
+///     &[
+///         Symbol::EH(Stress::Secondary),
+///         Symbol::F,
+///         Symbol::B,
+///         Symbol::IY(Stress::Secondary),
+///         Symbol::AY(Stress::Primary),
+///     ]
+/// );
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Acronyms {
+    overrides: HashMap<String, Reading>,
+}
+
+impl Acronyms {
+    /// Creates an empty list, so that every reading is guessed
+    pub fn new() -> Acronyms {
+        Acronyms::default()
+    }
+
+    /// Loads a list of acronyms from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Acronyms> {
+        let file = File::open(path)?;
+        Acronyms::read(BufReader::new(file))
+    }
+
+    fn read<R: BufRead>(reader: R) -> Result<Acronyms> {
+        let mut acronyms = Acronyms::new();
+        for line in crate::entry_lines(reader) {
+            let (idx, line) = line?;
+            let mut parts = line.split_whitespace();
+            let acronym = parts.next().ok_or(Error::InvalidLine(idx))?;
+            let rest: Vec<&str> = parts.collect();
+            let reading = match rest[..] {
+                [] => return Err(Error::InvalidLine(idx)),
+                ["letters"] => Reading::Letters,
+                ["word"] => Reading::Word,
+                _ => Reading::Pronunciation(
+                    rest.iter()
+                        .map(|s| Symbol::from_str(s))
+                        .collect::<std::result::Result<_, _>>()?,
+                ),
+            };
+            acronyms.insert(acronym, reading);
+        }
+        Ok(acronyms)
+    }
+
+    /// Sets how an acronym is read, whatever its case
+    pub fn insert(&mut self, acronym: &str, reading: Reading) {
+        self.overrides.insert(acronym.to_lowercase(), reading);
+    }
+
+    /// Returns the number of acronyms in the list
+    pub fn len(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Returns `true` if there are no acronyms in the list
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Decides how an acronym is read, from the list or else by guessing
+    ///
+    /// Periods between the letters are ignored, and acronyms written with them, like
+    /// "F.B.I.", are always spelled out unless the list says otherwise.
+    pub fn reading(&self, acronym: &str) -> Reading {
+        let lower = acronym.to_lowercase();
+        let letters: String = lower.chars().filter(|&c| c != '.').collect();
+        if let Some(reading) = self.overrides.get(&letters) {
+            return reading.clone();
+        }
+        if !lower.contains('.') && pronounceable(&letters) {
+            Reading::Word
+        } else {
+            Reading::Letters
+        }
+    }
+
+    /// Pronounces an acronym
+    ///
+    /// A spelled out acronym is built from the dictionary's entries for single letters, like
+    /// `a.` and `b.`, with the main stress on the last letter. An acronym read as a word is
+    /// looked up, or has its pronunciation guessed with `predictor` if it isn't in the
+    /// dictionary. A pronunciation from the list counts as being from the dictionary. Returns
+    /// `None` if the acronym should be spelled out but has something other than letters in it.
+    pub fn pronounce<'d, P>(
+        &self,
+        dict: &'d Cmudict,
+        acronym: &str,
+        predictor: &P,
+    ) -> Option<Lookup<'d>>
+    where
+        P: Predictor + ?Sized,
+    {
+        let label: String = acronym.to_lowercase().chars().filter(|&c| c != '.').collect();
+        match self.reading(acronym) {
+            Reading::Letters => {
+                let rule = Rule::new(label.as_str(), dict.spell_out(&label)?);
+                Some(Lookup::new(Cow::Owned(vec![rule]), Source::Derived))
+            }
+            Reading::Word => Some(dict.get_or_predict(&label, predictor)),
+            Reading::Pronunciation(phones) => {
+                let rule = Rule::new(label, phones);
+                Some(Lookup::new(Cow::Owned(vec![rule]), Source::Dictionary))
+            }
+        }
+    }
+}
+
+impl FromStr for Acronyms {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Acronyms> {
+        Acronyms::read(Cursor::new(s))
+    }
+}
+
+impl<S: AsRef<str>> std::iter::FromIterator<(S, Reading)> for Acronyms {
+    fn from_iter<I: IntoIterator<Item = (S, Reading)>>(iter: I) -> Acronyms {
+        let mut acronyms = Acronyms::new();
+        for (acronym, reading) in iter {
+            acronyms.insert(acronym.as_ref(), reading);
+        }
+        acronyms
+    }
+}
+
+impl Cmudict {
+    /// Spells a word out letter by letter, using the dictionary's entries for single letters
+    ///
+    /// Every letter but the last has its main stress lowered to secondary stress, as it is
+    /// in "F B I". Returns `None` if any letter has no entry, or if the word is empty.
+    pub fn spell_out(&self, word: &str) -> Option<Vec<Symbol>> {
+        let letters: Vec<char> = word.to_lowercase().chars().collect();
+        let mut phones = Vec::new();
+        for (idx, letter) in letters.iter().enumerate() {
+            let rules = self.get(&format!("{}.", letter))?;
+            let last = idx + 1 == letters.len();
+            phones.extend(rules.first()?.pronunciation().iter().map(|&symbol| {
+                if symbol.is_primary() && !last {
+                    symbol.with_stress(Stress::Secondary)
+                } else {
+                    symbol
+                }
+            }));
+        }
+        if phones.is_empty() {
+            None
+        } else {
+            Some(phones)
+        }
+    }
+}
+
+// whether a string of letters could be read as an English word
+fn pronounceable(word: &str) -> bool {
+    let letters: Vec<char> = word.chars().collect();
+    if letters.len() < 3 || !letters.iter().all(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    let is_vowel = |idx: usize| {
+        matches!(letters[idx], 'a' | 'e' | 'i' | 'o' | 'u') || (letters[idx] == 'y' && idx > 0)
+    };
+    // runs of vowels and of consonants
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for (idx, &c) in letters.iter().enumerate() {
+        match runs.last_mut() {
+            Some((vowel, run)) if *vowel == is_vowel(idx) => run.push(c),
+            _ => runs.push((is_vowel(idx), c.to_string())),
+        }
+    }
+    if !runs.iter().any(|&(vowel, _)| vowel) {
+        return false;
+    }
+    if letters.len() == 3 {
+        return runs.len() == 3 && !runs[0].0;
+    }
+    let last = runs.len() - 1;
+    runs.iter().enumerate().all(|(idx, (vowel, run))| {
+        let single = run.len() == 1;
+        match idx {
+            _ if *vowel => run.len() <= 2,
+            0 => single || ONSETS.contains(&run.as_str()),
+            _ if idx == last => single || CODAS.contains(&run.as_str()),
+            _ => run.len() <= 2,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::g2p::LetterRules;
+
+    #[test]
+    fn guessing() {
+        let acronyms = Acronyms::new();
+        for word in &["NASA", "NATO", "UNICEF", "SCUBA", "LASER", "GIF", "AIDS", "NASDAQ"] {
+            assert_eq!(acronyms.reading(word), Reading::Word, "{}", word);
+        }
+        for word in &["FBI", "HTTP", "CIA", "IBM", "USA", "CEO", "UK", "NAACP", "F.B.I", "IEEE"] {
+            assert_eq!(acronyms.reading(word), Reading::Letters, "{}", word);
+        }
+    }
+
+    #[test]
+    fn overriding() {
+        let acronyms: Acronyms = "NASA letters\n\n;; comment\nsql word\nGIF JH IH1 F"
+            .parse()
+            .expect("Could not parse acronyms");
+        assert_eq!(acronyms.len(), 3);
+        assert_eq!(acronyms.reading("NASA"), Reading::Letters);
+        assert_eq!(acronyms.reading("SQL"), Reading::Word);
+        assert_eq!(
+            acronyms.reading("gif"),
+            Reading::Pronunciation(vec![Symbol::JH, Symbol::IH(Stress::Primary), Symbol::F])
+        );
+        assert!("NASA".parse::<Acronyms>().is_err());
+        assert!("NASA XX1".parse::<Acronyms>().is_err());
+
+        let listed: Acronyms = vec![("ok", Reading::Letters)].into_iter().collect();
+        assert_eq!(listed.reading("OK"), Reading::Letters);
+    }
+
+    #[test]
+    fn pronouncing() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let acronyms = Acronyms::new();
+
+        let spelled = |s: &str| {
+            s.split_whitespace()
+                .map(|p| Symbol::from_str(p).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(d.spell_out("fbi"), Some(spelled("EH2 F B IY2 AY1")));
+        assert_eq!(d.spell_out("w"), Some(spelled("D AH1 B AH0 L Y UW0")));
+        assert_eq!(d.spell_out(""), None);
+        assert_eq!(d.spell_out("a1"), None);
+
+        let nasa = acronyms.pronounce(&d, "NASA", &rules).unwrap();
+        assert_eq!(nasa.source(), Source::Dictionary);
+        assert_eq!(nasa.rules(), d.get("nasa").unwrap());
+        let http = acronyms.pronounce(&d, "HTTP", &rules).unwrap();
+        assert_eq!(http.rules()[0].pronunciation(), &spelled("EY2 CH T IY2 T IY2 P IY1")[..]);
+        let dotted = acronyms.pronounce(&d, "F.B.I", &rules).unwrap();
+        assert_eq!(dotted.rules()[0].label(), "fbi");
+        assert!(acronyms.pronounce(&d, "A-1", &rules).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+//! Many-to-many alignment of spellings with pronunciations
+//!
+//! The aligner learns how likely each grapheme (a run of one to three letters) is to be
+//! pronounced as each run of zero to two phonemes, using expectation maximisation over every
+//! possible alignment of the words it is trained on. Silent letters are aligned with no
+//! phonemes at all, and only single letters can be silent, so a silent letter is folded into a
+//! longer grapheme such as "tch" or "kn" when that is more likely.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Rule, Symbol};
+use crate::g2p::normalize;
+use crate::Cmudict;
+
+const MAX_LETTERS: usize = 3;
+const MAX_PHONES: usize = 2;
+const ITERATIONS: usize = 5;
+
+/// Aligns the letters of words with the phonemes of their pronunciations
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Aligner, Cmudict, Symbol, Stress};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict: Cmudict = "
+/// cat K AE1 T
+/// hat HH AE1 T
+/// hit HH IH1 T
+/// hatch HH AE1 CH
+/// latch L AE1 CH
+/// lit L IH1 T
+/// ".trim().parse()?;
+/// let aligner = Aligner::train(&dict);
+///
+/// let hatch = &dict.get("hatch").unwrap()[0];
+/// assert_eq!(
+///     aligner.align("hatch", hatch),
+///     vec![
+///         ("h".to_string(), vec![Symbol::HH]),
+///         ("a".to_string(), vec![Symbol::AE(Stress::Primary)]),
+///         ("tch".to_string(), vec![Symbol::CH]),
+///     ]
+/// );
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Aligner {
+    probabilities: HashMap<u64, f64>,
+}
+
+impl Aligner {
+    /// Trains an aligner on every pronunciation in a dictionary
+    pub fn train(dict: &Cmudict) -> Aligner {
+        Aligner::train_from(dict.iter().flat_map(|(label, rules)| {
+            rules.iter().map(move |rule| (label, rule.pronunciation()))
+        }))
+    }
+
+    /// Trains an aligner on `(word, pronunciation)` pairs
+    pub fn train_from<'a, I>(entries: I) -> Aligner
+    where
+        I: IntoIterator<Item = (&'a str, &'a [Symbol])>,
+    {
+        let mut entries: Vec<(Vec<char>, &[Symbol])> = entries
+            .into_iter()
+            .map(|(word, phones)| (normalize(word), phones))
+            .collect();
+        // the counts are floating point sums, so they are added up in the same order however
+        // the entries come, for the alignments not to change between runs
+        entries.sort_by(|a, b| {
+            let symbols = |phones: &'a [Symbol]| phones.iter().map(|s| s.to_string());
+            a.0.cmp(&b.0).then_with(|| symbols(a.1).cmp(symbols(b.1)))
+        });
+
+        // start with every pairing equally likely
+        let mut aligner = Aligner {
+            probabilities: HashMap::new(),
+        };
+        for _ in 0..ITERATIONS {
+            let mut counts: HashMap<u64, f64> = HashMap::new();
+            for (word, phones) in &entries {
+                aligner.expect(word, phones, &mut counts);
+            }
+            let mut values: Vec<f64> = counts.values().copied().collect();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let total: f64 = values.iter().sum();
+            if total == 0.0 {
+                break;
+            }
+            aligner.probabilities = counts.into_iter().map(|(k, c)| (k, c / total)).collect();
+        }
+        aligner
+    }
+
+    /// Aligns a word with one of its pronunciations
+    ///
+    /// Each grapheme of the word is returned alongside the phonemes it produces. The graphemes
+    /// are lowercased, and the variant marker on labels like `every(2)` is ignored. If the word
+    /// can't be aligned with the pronunciation, for example because it has far more phonemes
+    /// than letters, the result is empty.
+    pub fn align(&self, word: &str, rule: &Rule) -> Vec<(String, Vec<Symbol>)> {
+        self.align_symbols(word, rule.pronunciation())
+    }
+
+    /// Aligns a word with a sequence of phonemes
+    pub fn align_symbols(&self, word: &str, phones: &[Symbol]) -> Vec<(String, Vec<Symbol>)> {
+        let word = normalize(word);
+        let (n, m) = (word.len(), phones.len());
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut best = vec![vec![f64::NEG_INFINITY; m + 1]; n + 1];
+        let mut back = vec![vec![(0, 0); m + 1]; n + 1];
+        best[0][0] = 0.0;
+        for i in 0..n {
+            for j in 0..=m {
+                if best[i][j] == f64::NEG_INFINITY {
+                    continue;
+                }
+                for (letters, count) in steps(&word[i..], &phones[j..]) {
+                    let p = self.probability(&word[i..i + letters], &phones[j..j + count]);
+                    if p <= 0.0 {
+                        continue;
+                    }
+                    let score = best[i][j] + p.ln();
+                    if score > best[i + letters][j + count] {
+                        best[i + letters][j + count] = score;
+                        back[i + letters][j + count] = (letters, count);
+                    }
+                }
+            }
+        }
+        if best[n][m] == f64::NEG_INFINITY {
+            return Vec::new();
+        }
+
+        let mut alignment = Vec::new();
+        let (mut i, mut j) = (n, m);
+        while i > 0 {
+            let (letters, count) = back[i][j];
+            alignment.push((
+                word[i - letters..i].iter().collect(),
+                phones[j - count..j].to_vec(),
+            ));
+            i -= letters;
+            j -= count;
+        }
+        alignment.reverse();
+        alignment
+    }
+
+    fn probability(&self, letters: &[char], phones: &[Symbol]) -> f64 {
+        if self.probabilities.is_empty() {
+            return 1.0;
+        }
+        self.probabilities.get(&key(letters, phones)).copied().unwrap_or(0.0)
+    }
+
+    // adds the expected number of times each pairing is used when aligning a word
+    fn expect(&self, word: &[char], phones: &[Symbol], counts: &mut HashMap<u64, f64>) {
+        let (n, m) = (word.len(), phones.len());
+        if n == 0 || m > n * MAX_PHONES {
+            return;
+        }
+        // (from, to, key, probability), ordered by where they start
+        let mut edges = Vec::new();
+        for i in 0..n {
+            for j in 0..=m {
+                for (letters, count) in steps(&word[i..], &phones[j..]) {
+                    let key = key(&word[i..i + letters], &phones[j..j + count]);
+                    let p = if self.probabilities.is_empty() {
+                        1.0
+                    } else {
+                        self.probabilities.get(&key).copied().unwrap_or(0.0)
+                    };
+                    if p > 0.0 {
+                        edges.push(((i, j), (i + letters, j + count), key, p));
+                    }
+                }
+            }
+        }
+
+        let mut forward = vec![vec![0.0; m + 1]; n + 1];
+        forward[0][0] = 1.0;
+        for &((i, j), (k, l), _, p) in &edges {
+            forward[k][l] += forward[i][j] * p;
+        }
+        let total = forward[n][m];
+        if total == 0.0 {
+            return;
+        }
+        let mut backward = vec![vec![0.0; m + 1]; n + 1];
+        backward[n][m] = 1.0;
+        for &((i, j), (k, l), _, p) in edges.iter().rev() {
+            backward[i][j] += p * backward[k][l];
+        }
+        for &((i, j), (k, l), key, p) in &edges {
+            let share = forward[i][j] * p * backward[k][l] / total;
+            if share > 0.0 {
+                *counts.entry(key).or_insert(0.0) += share;
+            }
+        }
+    }
+}
+
+// the (letters, phonemes) steps that can be taken from the start of `letters` and `phones`
+//
+// Several letters only ever make a single phoneme together, otherwise silent letters get
+// swallowed into graphemes with no phonemes and whole syllables get aligned at once. For the
+// same reason a grapheme of several letters has to be all vowels making a vowel sound, or all
+// consonants making a consonant sound, apart from "h", "w" and "y" which go either way.
+fn steps<'a>(letters: &'a [char], phones: &'a [Symbol]) -> impl Iterator<Item = (usize, usize)> + 'a {
+    (1..=MAX_LETTERS.min(letters.len())).flat_map(move |l| {
+        (0..=MAX_PHONES.min(phones.len()))
+            .filter(move |&p| l == 1 || (p == 1 && compatible(&letters[..l], phones[0])))
+            .map(move |p| (l, p))
+    })
+}
+
+fn compatible(letters: &[char], phone: Symbol) -> bool {
+    let vowel = |c: &&char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u');
+    let either = |c: &&char| matches!(c, 'h' | 'w' | 'y');
+    let mut letters = letters.iter().filter(|c| !either(c)).peekable();
+    match letters.peek() {
+        None => true,
+        Some(first) if vowel(first) => phone.is_syllable() && letters.all(|c| vowel(&c)),
+        Some(_) => !phone.is_syllable() && letters.all(|c| !vowel(&c)),
+    }
+}
+
+// packs a grapheme and phonemes into a key, ignoring stress
+fn key(letters: &[char], phones: &[Symbol]) -> u64 {
+    let mut key = 0u64;
+    for &c in letters {
+        let code = match c {
+            'a'..='z' => c as u64 - 'a' as u64 + 1,
+            '\'' => 27,
+            '-' => 28,
+            '.' => 29,
+            _ => 30,
+        };
+        key = (key << 5) | code;
+    }
+    for phone in phones {
+        key = (key << 6) | (phone.index() as u64 + 1);
+    }
+    // the lengths keep keys unambiguous
+    (key << 4) | ((letters.len() as u64) << 2) | phones.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Stress;
+
+    use pretty_assertions::assert_eq;
+
+    fn graphemes(alignment: &[(String, Vec<Symbol>)]) -> Vec<&str> {
+        alignment.iter().map(|(g, _)| &g[..]).collect()
+    }
+
+    #[test]
+    fn aligning() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let aligner = Aligner::train_from(crate::sample(&d, &["ca", "ma", "ta"]));
+
+        let catch = &d.get("catch").unwrap()[0];
+        assert_eq!(graphemes(&aligner.align("catch", catch)), vec!["c", "a", "tch"]);
+
+        let make = &d.get("make").unwrap()[0];
+        assert_eq!(
+            aligner.align("make", make),
+            vec![
+                ("m".to_string(), vec![Symbol::M]),
+                ("a".to_string(), vec![Symbol::EY(Stress::Primary)]),
+                ("k".to_string(), vec![Symbol::K]),
+                ("e".to_string(), vec![]),
+            ]
+        );
+
+        let tax = &d.get("tax").unwrap()[0];
+        assert_eq!(
+            aligner.align("tax", tax)[2],
+            ("x".to_string(), vec![Symbol::K, Symbol::S])
+        );
+
+        let kat = [Symbol::K, Symbol::AE(Stress::None), Symbol::T];
+        assert!(aligner.align_symbols("a", &kat).is_empty());
+    }
+}
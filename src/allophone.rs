@@ -0,0 +1,469 @@
+//! Surface pronunciations, with the allophones and connected speech of running speech
+//!
+//! The dictionary gives the phonemes of each word said on its own, but a forced aligner
+//! needs the phones that are actually said: the flap in "butter", the glottal stop in
+//! "button", and the changes words make to each other, like "did you" becoming "di-jou".
+//! `Allophones` turns the pronunciations of a sequence of words into TIMIT phones with rules
+//! written like those of a `Dialect`:
+//!
+//! ```text
+//! T -> dx / [vowel R] _ AH0
+//! D # Y -> # jh
+//! ```
+//!
+//! The targets and contexts are made of CMU symbols, and the replacements of TIMIT phones,
+//! where a vowel written without a stress takes the stress of the one it replaces. `#` is a
+//! boundary between words. Contexts look past boundaries, so rules apply across words as well
+//! as within them, while a `#` in a context only matches at the start or end of a word. A
+//! target with a `#` in it changes the end of one word and the start of the next; its
+//! replacement needs a `#` for each one in the target, to show where the words now split.
+//!
+//! The words are read from left to right. At every symbol the rules are tried in order, and
+//! the first whose target and contexts match replaces the symbols it covers. Symbols that no
+//! rule matches become the TIMIT phone closest to them. Contexts are always looked for in the
+//! pronunciations the rules started with, so a rule can't undo the work of another.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::core::Symbol;
+use crate::dialect::{invalid_rule, stress, RuleText};
+use crate::errors::{Error, ParseError, ParseResult, Result};
+use crate::pattern::{sequence, Item};
+use crate::text::{Boundary, Transcription};
+use crate::timit::Phone;
+
+// the rules of General American running speech
+const AMERICAN: &str = "
+;; \"want to\" loses its Ts, and \"got you\" and \"did you\" run together
+T # T -> # / N _ [vowel]
+T # Y -> # ch
+D # Y -> # jh
+;; T is a glottal stop before a syllabic N, as in \"button\", and before a consonant at the
+;; end of a word, as in \"that man\"
+T AH0 N -> q en / [vowel] _
+T -> q / [vowel] _ # [consonant]
+;; NT, T and D between vowels are flapped when the second vowel is unstressed
+N T -> nx / [vowel] _ [AA0 AE0 AH0 AO0 AW0 AY0 EH0 ER0 EY0 IH0 IY0 OW0 OY0 UH0 UW0]
+T -> dx / [vowel R] _ [AA0 AE0 AH0 AO0 AW0 AY0 EH0 ER0 EY0 IH0 IY0 OW0 OY0 UH0 UW0]
+D -> dx / [vowel R] _ [AA0 AE0 AH0 AO0 AW0 AY0 EH0 ER0 EY0 IH0 IY0 OW0 OY0 UH0 UW0]
+;; syllabic consonants
+AH0 L -> el / [consonant] _ #
+AH0 N -> en / [S Z] _ #
+;; reduced vowels
+AH0 -> ax
+IH0 -> ix
+ER0 -> axr
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Symbol(Symbol),
+    Boundary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    Phone(Phone),
+    // a vowel that takes the stress of the one it replaces
+    Base(Phone),
+    Boundary,
+}
+
+/// A rule that replaces symbols with the phones they are said as in some context
+///
+/// Rules are written as `target -> replacement / left _ right`, as described in the
+/// documentation of `Allophones`.
+#[derive(Debug, Clone)]
+pub struct AllophoneRule {
+    source: String,
+    target: Vec<Item>,
+    replacement: Vec<Output>,
+    left: Vec<Item>,
+    right: Vec<Item>,
+}
+
+impl AllophoneRule {
+    /// Compiles a rule
+    pub fn new(rule: &str) -> ParseResult<AllophoneRule> {
+        let invalid = |why: &str| invalid_rule(rule, why);
+        let RuleText { target, replacement, left, right } = RuleText::new(rule)?;
+        let target = sequence(target)?;
+        if target.is_empty() {
+            return Err(invalid("has nothing to replace"));
+        }
+        if target.iter().any(|item| matches!(item, Item::Start | Item::End)) {
+            return Err(invalid("has the start or end of the text in its target"));
+        }
+        let replacement = replacement
+            .split_whitespace()
+            .map(|label| {
+                if label == "#" {
+                    return Ok(Output::Boundary);
+                }
+                let phone = Phone::from_str(label)?;
+                if phone.is_vowel() && !label.ends_with(|c: char| c.is_ascii_digit()) {
+                    Ok(Output::Base(phone))
+                } else {
+                    Ok(Output::Phone(phone))
+                }
+            })
+            .collect::<ParseResult<Vec<Output>>>()?;
+        let boundaries = target.iter().filter(|item| matches!(item, Item::Boundary)).count();
+        let splits = replacement.iter().filter(|&&output| output == Output::Boundary).count();
+        if splits != boundaries {
+            return Err(invalid("changes the number of words"));
+        }
+        Ok(AllophoneRule {
+            source: rule.trim().to_string(),
+            target,
+            replacement,
+            left,
+            right,
+        })
+    }
+
+    // where the target ends if the rule matches at `pos`
+    fn matches(&self, tokens: &[Token], pos: usize) -> Option<usize> {
+        let mut end = pos;
+        for item in &self.target {
+            match (item, tokens.get(end)) {
+                (Item::Symbol(matcher), Some(Token::Symbol(symbol))) => {
+                    if !matcher.matches(symbol) {
+                        return None;
+                    }
+                }
+                (Item::Boundary, Some(Token::Boundary)) => {}
+                _ => return None,
+            }
+            end += 1;
+        }
+        if before(&self.left, tokens, pos) && after(&self.right, tokens, end) {
+            Some(end)
+        } else {
+            None
+        }
+    }
+
+    // adds the replacement for the matched tokens to the phones of the words
+    fn replace(&self, matched: &[Token], words: &mut Vec<Vec<Phone>>) {
+        let symbols: Vec<Symbol> = matched
+            .iter()
+            .filter_map(|token| match *token {
+                Token::Symbol(symbol) => Some(symbol),
+                Token::Boundary => None,
+            })
+            .collect();
+        let mut idx = 0;
+        for output in &self.replacement {
+            let phone = match *output {
+                Output::Boundary => {
+                    words.push(Vec::new());
+                    continue;
+                }
+                Output::Phone(phone) => phone,
+                Output::Base(phone) => phone.with_stress(stress(&symbols, idx)),
+            };
+            words.last_mut().expect("there is always a word").push(phone);
+            idx += 1;
+        }
+    }
+}
+
+// whether a left context matches the symbols before `pos`, looking past word boundaries
+fn before(items: &[Item], tokens: &[Token], mut pos: usize) -> bool {
+    for item in items.iter().rev() {
+        match item {
+            Item::Start if pos != 0 => return false,
+            Item::End if pos != tokens.len() => return false,
+            Item::Boundary if pos > 0 => {
+                if tokens[pos - 1] != Token::Boundary {
+                    return false;
+                }
+                pos -= 1;
+            }
+            Item::Symbol(matcher) => {
+                while pos > 0 && tokens[pos - 1] == Token::Boundary {
+                    pos -= 1;
+                }
+                match tokens[..pos].last() {
+                    Some(Token::Symbol(symbol)) if matcher.matches(symbol) => pos -= 1,
+                    _ => return false,
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+// whether a right context matches the symbols from `pos` on, looking past word boundaries
+fn after(items: &[Item], tokens: &[Token], mut pos: usize) -> bool {
+    for item in items {
+        match item {
+            Item::Start if pos != 0 => return false,
+            Item::End if pos != tokens.len() => return false,
+            Item::Boundary if pos < tokens.len() => {
+                if tokens[pos] != Token::Boundary {
+                    return false;
+                }
+                pos += 1;
+            }
+            Item::Symbol(matcher) => {
+                while tokens.get(pos) == Some(&Token::Boundary) {
+                    pos += 1;
+                }
+                match tokens.get(pos) {
+                    Some(Token::Symbol(symbol)) if matcher.matches(symbol) => pos += 1,
+                    _ => return false,
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+impl FromStr for AllophoneRule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<AllophoneRule> {
+        AllophoneRule::new(s)
+    }
+}
+
+impl fmt::Display for AllophoneRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Rules that turn the pronunciations of words into the phones of running speech
+///
+/// Rules can be read from text with one rule per line. Blank lines and lines starting with
+/// `;;` are skipped.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Allophones, Cmudict, Phone, Stress};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let phones = |word: &str| dict.get(word).unwrap()[0].pronunciation();
+/// let american = Allophones::american();
+///
+/// assert_eq!(
+///     american.surface(phones("butter")),
+///     [Phone::B, Phone::AH(Stress::Primary), Phone::DX, Phone::AXR(Stress::None)]
+/// );
+///
+/// let did_you = american.apply(&[phones("did"), phones("you")]);
+/// assert_eq!(did_you[0], [Phone::D, Phone::IH(Stress::Primary)]);
+/// assert_eq!(did_you[1], [Phone::JH, Phone::UW(Stress::Primary)]);
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Allophones {
+    rules: Vec<AllophoneRule>,
+}
+
+impl Allophones {
+    /// Creates a set with no rules, which turns each symbol into the TIMIT phone closest to it
+    pub fn new() -> Allophones {
+        Allophones::default()
+    }
+
+    /// Creates the built-in rules for General American: flapping, glottal stops, syllabic
+    /// consonants, reduced vowels, and a few changes across words
+    pub fn american() -> Allophones {
+        AMERICAN.parse().expect("built-in rules are valid")
+    }
+
+    /// Loads rules from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Allophones> {
+        let file = File::open(path)?;
+        Allophones::read(BufReader::new(file))
+    }
+
+    fn read<R: BufRead>(reader: R) -> Result<Allophones> {
+        let mut allophones = Allophones::new();
+        for line in crate::entry_lines(reader) {
+            let (_, line) = line?;
+            allophones.push(line.parse()?);
+        }
+        Ok(allophones)
+    }
+
+    /// Adds a rule, to be tried after the rules already in the set
+    pub fn push(&mut self, rule: AllophoneRule) {
+        self.rules.push(rule);
+    }
+
+    /// Returns the rules, in the order they are tried
+    pub fn rules(&self) -> &[AllophoneRule] {
+        &self.rules
+    }
+
+    /// Returns the number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the phones of a word said on its own
+    pub fn surface(&self, phones: &[Symbol]) -> Vec<Phone> {
+        self.apply(&[phones]).remove(0)
+    }
+
+    /// Returns the phones of each of a sequence of words said together
+    pub fn apply<W: AsRef<[Symbol]>>(&self, words: &[W]) -> Vec<Vec<Phone>> {
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let mut tokens = Vec::new();
+        for (idx, word) in words.iter().enumerate() {
+            if idx > 0 {
+                tokens.push(Token::Boundary);
+            }
+            tokens.extend(word.as_ref().iter().map(|&symbol| Token::Symbol(symbol)));
+        }
+
+        let mut surface = vec![Vec::new()];
+        let mut pos = 0;
+        while pos < tokens.len() {
+            let found = self
+                .rules
+                .iter()
+                .find_map(|rule| rule.matches(&tokens, pos).map(|end| (rule, end)));
+            if let Some((rule, end)) = found {
+                rule.replace(&tokens[pos..end], &mut surface);
+                pos = end;
+                continue;
+            }
+            match tokens[pos] {
+                Token::Symbol(symbol) => {
+                    surface.last_mut().expect("there is always a word").push(symbol.into())
+                }
+                Token::Boundary => surface.push(Vec::new()),
+            }
+            pos += 1;
+        }
+        surface
+    }
+}
+
+impl FromStr for Allophones {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Allophones> {
+        Allophones::read(Cursor::new(s))
+    }
+}
+
+impl Transcription {
+    /// Returns the phones of each word of the transcription, as `Allophones::apply` gives
+    /// them
+    ///
+    /// Words run together within a phrase, but rules don't apply across the boundaries of
+    /// phrases and sentences, where a speaker would pause.
+    pub fn surface(&self, allophones: &Allophones) -> Vec<Vec<Phone>> {
+        let mut surface = Vec::with_capacity(self.words().len());
+        let mut phrase = Vec::new();
+        for word in self.words() {
+            phrase.push(word.pronunciation());
+            if word.boundary() > Boundary::Word {
+                surface.extend(allophones.apply(&phrase));
+                phrase.clear();
+            }
+        }
+        surface.extend(allophones.apply(&phrase));
+        surface
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Stress;
+    use crate::g2p::LetterRules;
+    use crate::Cmudict;
+
+    fn labels(phones: &[Phone]) -> String {
+        phones.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn american() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let american = Allophones::american();
+        let word = |w: &str| labels(&american.surface(d.get(w).unwrap()[0].pronunciation()));
+        assert_eq!(word("butter"), "b ah1 dx axr");
+        assert_eq!(word("button"), "b ah1 q en");
+        assert_eq!(word("winter"), "w ih1 nx axr");
+        assert_eq!(word("bottle"), "b aa1 dx el");
+        assert_eq!(word("season"), "s iy1 z en");
+        assert_eq!(word("get"), "g eh1 t");
+
+        let words = |text: &str| {
+            let words: Vec<&[Symbol]> =
+                text.split(' ').map(|w| d.get(w).unwrap()[0].pronunciation()).collect();
+            american.apply(&words).iter().map(|p| labels(p)).collect::<Vec<_>>()
+        };
+        assert_eq!(words("get a"), ["g eh1 dx", "ax"]);
+        assert_eq!(words("that man"), ["dh ae1 q", "m ae1 n"]);
+        assert_eq!(words("want to"), ["w aa1 n", "uw1"]);
+        assert_eq!(words("got you"), ["g aa1", "ch uw1"]);
+
+        assert!(american.apply::<&[Symbol]>(&[]).is_empty());
+        assert_eq!(american.apply(&[vec![], vec![Symbol::T]]), vec![vec![], vec![Phone::T]]);
+    }
+
+    #[test]
+    fn transcriptions() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let american = Allophones::american();
+        let surface = |text: &str| {
+            let transcription = d.transcribe(text, &rules);
+            transcription.surface(&american).iter().map(|p| labels(p)).collect::<Vec<_>>()
+        };
+        assert_eq!(surface("that man"), ["dh ae1 q", "m ae1 n"]);
+        assert_eq!(surface("that, man"), ["dh ae1 t", "m ae1 n"]);
+        assert!(surface("").is_empty());
+    }
+
+    #[test]
+    fn rules() {
+        let rules: Allophones = ";; raising\nAY -> ah / _ [voiceless]".parse().unwrap();
+        assert_eq!(rules.rules()[0].to_string(), "AY -> ah / _ [voiceless]");
+        let write = [Symbol::R, Symbol::AY(Stress::Secondary), Symbol::T];
+        assert_eq!(labels(&rules.surface(&write)), "r ah2 t");
+        let ride = [Symbol::R, Symbol::AY(Stress::Secondary), Symbol::D];
+        assert_eq!(labels(&rules.surface(&ride)), "r ay2 d");
+
+        assert!("T # -> dx".parse::<AllophoneRule>().is_err());
+        assert!("^ T -> q".parse::<AllophoneRule>().is_err());
+        assert!("T -> zz".parse::<AllophoneRule>().is_err());
+        assert!("T -> q / V".parse::<AllophoneRule>().is_err());
+        assert!("-> q".parse::<AllophoneRule>().is_err());
+        assert!(";; none\n\n".parse::<Allophones>().unwrap().is_empty());
+    }
+}
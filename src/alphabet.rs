@@ -0,0 +1,386 @@
+//! Writing and reading pronunciations in different phonetic alphabets
+
+use std::str::FromStr;
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{ParseError, ParseResult};
+use crate::ipa::{parse_ipa, IpaStyle};
+use crate::syllable::syllabify;
+
+/// A way of writing down pronunciations
+pub trait PhoneticAlphabet {
+    /// Writes a single symbol
+    fn symbol(&self, symbol: &Symbol) -> String;
+
+    /// Writes a whole pronunciation, including any stress marks the alphabet uses
+    fn write(&self, phones: &[Symbol]) -> String;
+
+    /// Reads a pronunciation written in the alphabet
+    fn read(&self, s: &str) -> ParseResult<Vec<Symbol>>;
+}
+
+impl Rule {
+    /// Writes the pronunciation in the given alphabet
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Arpabet, Cmudict, Kirshenbaum, XSampa};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let apple = &dict.get("apple").unwrap()[0];
+    /// assert_eq!(apple.transcribe(&Arpabet), "AE1 P AH0 L");
+    /// assert_eq!(apple.transcribe(&XSampa), "\"{p@l");
+    /// assert_eq!(apple.transcribe(&Kirshenbaum), "'&p@l");
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe<A: PhoneticAlphabet + ?Sized>(&self, alphabet: &A) -> String {
+        alphabet.write(self.pronunciation())
+    }
+}
+
+/// The ARPAbet symbols used by the dictionary itself, separated by spaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Arpabet;
+
+impl PhoneticAlphabet for Arpabet {
+    fn symbol(&self, symbol: &Symbol) -> String {
+        symbol.to_string()
+    }
+
+    fn write(&self, phones: &[Symbol]) -> String {
+        let symbols: Vec<String> = phones.iter().map(Symbol::to_string).collect();
+        symbols.join(" ")
+    }
+
+    fn read(&self, s: &str) -> ParseResult<Vec<Symbol>> {
+        s.split_whitespace().map(Symbol::from_str).collect()
+    }
+}
+
+impl PhoneticAlphabet for IpaStyle {
+    fn symbol(&self, symbol: &Symbol) -> String {
+        symbol.to_ipa(self)
+    }
+
+    fn write(&self, phones: &[Symbol]) -> String {
+        crate::ipa::to_ipa(phones, self)
+    }
+
+    fn read(&self, s: &str) -> ParseResult<Vec<Symbol>> {
+        parse_ipa(s)
+    }
+}
+
+/// X-SAMPA, an ASCII rendering of the IPA
+///
+/// Unstressed `AH` and `ER` are written as the reduced vowels `@` and `` @` ``. Primary stress
+/// is marked with `"` and secondary stress with `%` before the stressed syllable, even in words
+/// of one syllable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct XSampa;
+
+// (stressed or full, unstressed)
+fn x_sampa(symbol: Symbol) -> (&'static str, &'static str) {
+    use crate::core::Symbol::*;
+    let full = match symbol {
+        AA(_) => "A",
+        AE(_) => "{",
+        AH(_) => return ("V", "@"),
+        AO(_) => "O",
+        AW(_) => "aU",
+        AY(_) => "aI",
+        B => "b",
+        CH => "tS",
+        D => "d",
+        DH => "D",
+        EH(_) => "E",
+        ER(_) => return ("3`", "@`"),
+        EY(_) => "eI",
+        F => "f",
+        G => "g",
+        HH => "h",
+        IH(_) => "I",
+        IY(_) => "i",
+        JH => "dZ",
+        K => "k",
+        L => "l",
+        M => "m",
+        N => "n",
+        NG => "N",
+        OW(_) => "oU",
+        OY(_) => "OI",
+        P => "p",
+        R => "r\\",
+        S => "s",
+        SH => "S",
+        T => "t",
+        TH => "T",
+        UH(_) => "U",
+        UW(_) => "u",
+        V => "v",
+        W => "w",
+        Y => "j",
+        Z => "z",
+        ZH => "Z",
+    };
+    (full, full)
+}
+
+const X_SAMPA_EXTRA: &[(&str, Symbol)] = &[
+    ("3", Symbol::ER(Stress::None)),
+    ("r", Symbol::R),
+    ("e", Symbol::EY(Stress::None)),
+    ("o", Symbol::OW(Stress::None)),
+    ("Q", Symbol::AA(Stress::None)),
+    ("5", Symbol::L),
+];
+
+impl PhoneticAlphabet for XSampa {
+    fn symbol(&self, symbol: &Symbol) -> String {
+        pick(x_sampa(*symbol), symbol).to_string()
+    }
+
+    fn write(&self, phones: &[Symbol]) -> String {
+        write_marked(phones, "\"", "%", |s| pick(x_sampa(*s), s))
+    }
+
+    /// Reads X-SAMPA
+    ///
+    /// Stress marks give their stress to the next vowel. Syllable breaks (`.`), length marks
+    /// (`:`), spaces, slashes and brackets are ignored, and `3`, `r`, `e`, `o`, `Q` and `5` are
+    /// understood as `ER`, `R`, `EY`, `OW`, `AA` and `L`. The longest symbol that matches is
+    /// always taken, so `tS` is read as `CH` rather than `T` followed by `SH`.
+    fn read(&self, s: &str) -> ParseResult<Vec<Symbol>> {
+        read_marked(s, x_sampa, X_SAMPA_EXTRA, '"', '%', "X-SAMPA")
+    }
+}
+
+/// The Kirshenbaum ASCII-IPA alphabet, which eSpeak's phoneme input is based on
+///
+/// Unstressed `AH` is written as the reduced vowel `@`. Primary stress is marked with `'` and
+/// secondary stress with `,` before the stressed syllable, even in words of one syllable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Kirshenbaum;
+
+fn kirshenbaum(symbol: Symbol) -> (&'static str, &'static str) {
+    use crate::core::Symbol::*;
+    let full = match symbol {
+        AA(_) => "A",
+        AE(_) => "&",
+        AH(_) => return ("V", "@"),
+        AO(_) => "O",
+        AW(_) => "aU",
+        AY(_) => "aI",
+        B => "b",
+        CH => "tS",
+        D => "d",
+        DH => "D",
+        EH(_) => "E",
+        ER(_) => "R",
+        EY(_) => "eI",
+        F => "f",
+        G => "g",
+        HH => "h",
+        IH(_) => "I",
+        IY(_) => "i",
+        JH => "dZ",
+        K => "k",
+        L => "l",
+        M => "m",
+        N => "n",
+        NG => "N",
+        OW(_) => "oU",
+        OY(_) => "OI",
+        P => "p",
+        R => "r",
+        S => "s",
+        SH => "S",
+        T => "t",
+        TH => "T",
+        UH(_) => "U",
+        UW(_) => "u",
+        V => "v",
+        W => "w",
+        Y => "j",
+        Z => "z",
+        ZH => "Z",
+    };
+    (full, full)
+}
+
+const KIRSHENBAUM_EXTRA: &[(&str, Symbol)] = &[
+    ("3", Symbol::ER(Stress::None)),
+    ("e", Symbol::EY(Stress::None)),
+    ("o", Symbol::OW(Stress::None)),
+];
+
+impl PhoneticAlphabet for Kirshenbaum {
+    fn symbol(&self, symbol: &Symbol) -> String {
+        pick(kirshenbaum(*symbol), symbol).to_string()
+    }
+
+    fn write(&self, phones: &[Symbol]) -> String {
+        write_marked(phones, "'", ",", |s| pick(kirshenbaum(*s), s))
+    }
+
+    /// Reads Kirshenbaum
+    ///
+    /// Stress marks give their stress to the next vowel. Syllable breaks (`.`), length marks
+    /// (`:`), spaces, slashes and brackets are ignored, and `3`, `e` and `o` are understood as
+    /// `ER`, `EY` and `OW`.
+    fn read(&self, s: &str) -> ParseResult<Vec<Symbol>> {
+        read_marked(s, kirshenbaum, KIRSHENBAUM_EXTRA, '\'', ',', "Kirshenbaum")
+    }
+}
+
+fn pick(forms: (&'static str, &'static str), symbol: &Symbol) -> &'static str {
+    if symbol.is_syllable() && symbol.is_unstressed() {
+        forms.1
+    } else {
+        forms.0
+    }
+}
+
+// every symbol, with the vowels unstressed
+pub(crate) const ALL: [Symbol; 39] = {
+    use crate::core::Symbol::*;
+    let s = Stress::None;
+    [
+        AA(s), AE(s), AH(s), AO(s), AW(s), AY(s), B, CH, D, DH, EH(s), ER(s), EY(s), F, G, HH,
+        IH(s), IY(s), JH, K, L, M, N, NG, OW(s), OY(s), P, R, S, SH, T, TH, UH(s), UW(s), V, W,
+        Y, Z, ZH,
+    ]
+};
+
+// writes a pronunciation with a stress mark before every stressed syllable
+fn write_marked<F>(phones: &[Symbol], primary: &str, secondary: &str, write: F) -> String
+where
+    F: Fn(&Symbol) -> &'static str,
+{
+    let syllables = syllabify(phones);
+    if syllables.is_empty() {
+        return phones.iter().map(write).collect();
+    }
+    let mut out = String::new();
+    for syllable in &syllables {
+        match syllable.stress() {
+            Stress::Primary => out.push_str(primary),
+            Stress::Secondary => out.push_str(secondary),
+            Stress::None => {}
+        }
+        for symbol in syllable.symbols() {
+            out.push_str(write(&symbol));
+        }
+    }
+    out
+}
+
+// reads an ASCII alphabet by always taking the longest symbol that matches
+fn read_marked(
+    s: &str,
+    forms: fn(Symbol) -> (&'static str, &'static str),
+    extra: &[(&str, Symbol)],
+    primary: char,
+    secondary: char,
+    name: &'static str,
+) -> ParseResult<Vec<Symbol>> {
+    let mut table: Vec<(&str, Symbol)> = Vec::new();
+    for &symbol in ALL.iter() {
+        let (full, reduced) = forms(symbol);
+        table.push((full, symbol));
+        if reduced != full {
+            table.push((reduced, symbol));
+        }
+    }
+    table.extend_from_slice(extra);
+    table.sort_by_key(|(text, _)| std::cmp::Reverse(text.len()));
+
+    let mut symbols = Vec::new();
+    let mut stress = Stress::None;
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        if c == primary || c == secondary {
+            stress = if c == primary { Stress::Primary } else { Stress::Secondary };
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if c.is_whitespace() || matches!(c, '.' | ':' | '/' | '[' | ']') {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let (text, symbol) = table
+            .iter()
+            .find(|(text, _)| rest.starts_with(text))
+            .ok_or(ParseError::UnexpectedCharacter(name, c))?;
+        let symbol = if symbol.is_syllable() {
+            let symbol = symbol.with_stress(stress);
+            stress = Stress::None;
+            symbol
+        } else {
+            *symbol
+        };
+        symbols.push(symbol);
+        rest = &rest[text.len()..];
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+    use crate::Cmudict;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trips() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let alphabets: [&dyn PhoneticAlphabet; 4] =
+            [&Arpabet, &XSampa, &Kirshenbaum, &IpaStyle::default()];
+        let words = ["butter", "church", "abstract", "outside", "thoroughly", "measure", "rust"];
+        for word in &words {
+            for rule in d.get(word).unwrap() {
+                for alphabet in alphabets.iter() {
+                    let written = rule.transcribe(*alphabet);
+                    assert_eq!(alphabet.read(&written).unwrap(), rule.pronunciation());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(XSampa.symbol(&ER(Stress::Primary)), "3`");
+        assert_eq!(XSampa.symbol(&ER(Stress::None)), "@`");
+        assert_eq!(XSampa.symbol(&R), "r\\");
+        assert_eq!(Kirshenbaum.symbol(&AE(Stress::None)), "&");
+        assert_eq!(Arpabet.symbol(&AE(Stress::Secondary)), "AE2");
+
+        assert_eq!(
+            XSampa.read("\"bV.t@`"),
+            Ok(vec![B, AH(Stress::Primary), T, ER(Stress::None)])
+        );
+        assert_eq!(
+            Kirshenbaum.read("'tSRtS"),
+            Ok(vec![CH, ER(Stress::Primary), CH])
+        );
+        assert_eq!(
+            XSampa.read("k{x"),
+            Err(ParseError::UnexpectedCharacter("X-SAMPA", 'x'))
+        );
+        assert!(Arpabet.read("K AE1 X").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Splitting compound words into words in the dictionary
+//!
+//! Compounds like "sunflowerseed" are often missing from the dictionary when their parts are
+//! not. The fewest parts that can cover each suffix of a word are found by dynamic
+//! programming over the labels of the dictionary, and that count bounds the search for every
+//! segmentation, so words that split into many short parts don't take long to give up on.
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::Cmudict;
+
+// the shortest part a word is split into, which keeps out the many two letter entries
+const MIN_PART: usize = 3;
+
+// how many more parts than the fewest a segmentation can have
+const SLACK: usize = 1;
+
+/// A way of splitting a word into words in the dictionary
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segmentation<'a> {
+    parts: Vec<&'a str>,
+    rule: Rule,
+}
+
+impl<'a> Segmentation<'a> {
+    /// Returns the words the word was split into, in order
+    pub fn parts(&self) -> &[&'a str] {
+        &self.parts
+    }
+
+    /// Returns the pronunciation built from the parts
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Returns the symbols of the pronunciation built from the parts
+    pub fn pronunciation(&self) -> &[Symbol] {
+        self.rule.pronunciation()
+    }
+
+    /// Consumes the segmentation, returning the pronunciation built from its parts
+    pub fn into_rule(self) -> Rule {
+        self.rule
+    }
+}
+
+impl Cmudict {
+    /// Splits a word into two or more words in the dictionary
+    ///
+    /// Every part has at least three letters. The pronunciations of the parts are joined with
+    /// the stress of a compound: the first primary stress is kept, and those after it are
+    /// lowered to secondary, as in "sunflower". Segmentations with the fewest parts come
+    /// first, and among them, those whose shortest part is longest, so "sun flower seed" comes
+    /// before "sun flow erseed" would. Segmentations with one more part than the fewest are
+    /// returned too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, Stress, Symbol};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// assert!(dict.get("sunflowerseed").is_none());
+    ///
+    /// let segmentations = dict.decompose("sunflowerseed");
+    /// assert_eq!(segmentations[0].parts(), ["sunflower", "seed"]);
+    /// let seed = [Symbol::S, Symbol::IY(Stress::Secondary), Symbol::D];
+    /// assert!(segmentations[0].pronunciation().ends_with(&seed));
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn decompose(&self, word: &str) -> Vec<Segmentation<'_>> {
+        let word = word.to_lowercase();
+        let bounds: Vec<usize> =
+            word.char_indices().map(|(idx, _)| idx).chain(Some(word.len())).collect();
+        let n = bounds.len() - 1;
+        if n < 2 * MIN_PART {
+            return Vec::new();
+        }
+
+        // the labels starting at each letter, as the letter they end before and the label
+        let mut labels: Vec<Vec<(usize, &str)>> = vec![Vec::new(); n];
+        for (i, starts) in labels.iter_mut().enumerate() {
+            // longest first, so longer parts are tried first
+            for j in (i + MIN_PART..=n).rev() {
+                if i == 0 && j == n {
+                    continue;
+                }
+                if let Some((label, _)) = self.map.get_key_value(&word[bounds[i]..bounds[j]]) {
+                    starts.push((j, label.as_str()));
+                }
+            }
+        }
+
+        // the fewest labels that cover the word from each letter on
+        let mut fewest: Vec<Option<usize>> = vec![None; n + 1];
+        fewest[n] = Some(0);
+        for i in (0..n).rev() {
+            fewest[i] = labels[i].iter().filter_map(|&(j, _)| fewest[j]).min().map(|f| f + 1);
+        }
+        let limit = match fewest[0] {
+            Some(f) => f + SLACK,
+            None => return Vec::new(),
+        };
+
+        let mut found = Vec::new();
+        self.segment(&labels, &fewest, 0, limit, &mut Vec::new(), &mut found);
+        let mut segmentations: Vec<Segmentation<'_>> = found
+            .into_iter()
+            .map(|parts| {
+                let rule = Rule::new(word.as_str(), self.join(&parts));
+                Segmentation { parts, rule }
+            })
+            .collect();
+        let shortest = |s: &Segmentation<'_>| s.parts.iter().map(|p| p.len()).min();
+        segmentations
+            .sort_by(|a, b| (a.parts.len(), shortest(b)).cmp(&(b.parts.len(), shortest(a))));
+        segmentations
+    }
+
+    // collects every way of covering the word from letter `i` on in no more than `limit` parts
+    fn segment<'d>(
+        &'d self,
+        labels: &[Vec<(usize, &'d str)>],
+        fewest: &[Option<usize>],
+        i: usize,
+        limit: usize,
+        parts: &mut Vec<&'d str>,
+        found: &mut Vec<Vec<&'d str>>,
+    ) {
+        if i == labels.len() {
+            found.push(parts.clone());
+            return;
+        }
+        for &(j, label) in &labels[i] {
+            match fewest[j] {
+                Some(f) if parts.len() + 1 + f <= limit => {}
+                _ => continue,
+            }
+            parts.push(label);
+            self.segment(labels, fewest, j, limit, parts, found);
+            parts.pop();
+        }
+    }
+
+    // joins the first pronunciations of the parts, keeping only the first primary stress
+    fn join(&self, parts: &[&str]) -> Vec<Symbol> {
+        let mut stressed = false;
+        let mut phones = Vec::new();
+        for part in parts {
+            let rules = self.map.get(*part).map(Vec::as_slice).unwrap_or_default();
+            for &symbol in rules.first().map(Rule::pronunciation).unwrap_or_default() {
+                if symbol.is_primary() {
+                    if stressed {
+                        phones.push(symbol.with_stress(Stress::Secondary));
+                        continue;
+                    }
+                    stressed = true;
+                }
+                phones.push(symbol);
+            }
+        }
+        phones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compounds() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let segmentations = d.decompose("sunflowerseed");
+        assert_eq!(segmentations[0].parts(), ["sunflower", "seed"]);
+        assert!(segmentations.iter().any(|s| s.parts() == ["sun", "flower", "seed"]));
+        assert!(segmentations.iter().all(|s| s.parts().len() <= 3));
+        assert!(segmentations.iter().all(|s| s.rule().label() == "sunflowerseed"));
+
+        // a compound in the dictionary splits into its parts, with the same stress
+        let blackboard = d.decompose("Blackboard");
+        assert_eq!(blackboard[0].parts(), ["black", "board"]);
+        assert_eq!(blackboard[0].pronunciation(), d.get("blackboard").unwrap()[0].pronunciation());
+
+        assert!(d.decompose("blonk").is_empty());
+        assert!(d.decompose("seed").is_empty());
+        assert!(d.decompose("").is_empty());
+    }
+
+    #[test]
+    fn stress() {
+        use Symbol::*;
+        let d: Cmudict = "key K IY1\nboard B AO1 R D\na AH0\n".parse().unwrap();
+        let keyboard = d.join(&["key", "board"]);
+        assert_eq!(keyboard, vec![K, IY(Stress::Primary), B, AO(Stress::Secondary), R, D]);
+        let aboard = d.join(&["a", "board"]);
+        assert_eq!(aboard, vec![AH(Stress::None), B, AO(Stress::Primary), R, D]);
+    }
+}
@@ -12,7 +12,7 @@ use serde::{Serialize, Deserialize};
 use super::errors::{ParseError, ParseResult};
 
 /// Used by a symbol to indicate what kind of stress it has
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[allow(missing_docs)]
 pub enum Stress {
@@ -22,7 +22,7 @@ pub enum Stress {
 }
 
 /// Represents a single sound
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[allow(missing_docs)]
 pub enum Symbol {
@@ -69,6 +69,7 @@ pub enum Symbol {
 
 impl Symbol {
     /// Returns `true` if the symbol has primary stress
+    #[allow(clippy::match_like_matches_macro)]
     pub fn is_primary(&self) -> bool {
         use self::Symbol::*;
         match self {
@@ -92,6 +93,7 @@ impl Symbol {
     }
 
     /// Returns `true` if the symbol has secondary stress
+    #[allow(clippy::match_like_matches_macro)]
     pub fn is_secondary(&self) -> bool {
         use self::Symbol::*;
         match self {
@@ -115,6 +117,7 @@ impl Symbol {
     }
 
     /// Returns `true` if the symbol has no stress
+    #[allow(clippy::match_like_matches_macro)]
     pub fn is_unstressed(&self) -> bool {
         use self::Symbol::*;
         match self {
@@ -153,6 +156,7 @@ impl Symbol {
     }
 
     /// Returns `true` if the symbol is stressed in some way
+    #[allow(clippy::match_like_matches_macro)]
     pub fn is_syllable(&self) -> bool {
         use self::Symbol::*;
         match self {
@@ -174,6 +178,100 @@ impl Symbol {
             _ => false
         }
     }
+
+    /// Returns the stress of the symbol, or `None` if it is a consonant
+    pub fn stress(&self) -> Option<Stress> {
+        use self::Symbol::*;
+        match *self {
+            | AA(s)
+            | AE(s)
+            | AH(s)
+            | AO(s)
+            | AW(s)
+            | AY(s)
+            | EH(s)
+            | ER(s)
+            | EY(s)
+            | IH(s)
+            | IY(s)
+            | OW(s)
+            | OY(s)
+            | UH(s)
+            | UW(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of the symbol carrying the given stress
+    ///
+    /// Consonants have no stress, so they are returned unchanged.
+    pub fn with_stress(&self, stress: Stress) -> Symbol {
+        use self::Symbol::*;
+        match *self {
+            AA(_) => AA(stress),
+            AE(_) => AE(stress),
+            AH(_) => AH(stress),
+            AO(_) => AO(stress),
+            AW(_) => AW(stress),
+            AY(_) => AY(stress),
+            EH(_) => EH(stress),
+            ER(_) => ER(stress),
+            EY(_) => EY(stress),
+            IH(_) => IH(stress),
+            IY(_) => IY(stress),
+            OW(_) => OW(stress),
+            OY(_) => OY(stress),
+            UH(_) => UH(stress),
+            UW(_) => UW(stress),
+            other => other,
+        }
+    }
+
+    // a number from 0 to 38 identifying the symbol, ignoring stress
+    pub(crate) fn index(&self) -> usize {
+        use self::Symbol::*;
+        match *self {
+            AA(_) => 0,
+            AE(_) => 1,
+            AH(_) => 2,
+            AO(_) => 3,
+            AW(_) => 4,
+            AY(_) => 5,
+            B => 6,
+            CH => 7,
+            D => 8,
+            DH => 9,
+            EH(_) => 10,
+            ER(_) => 11,
+            EY(_) => 12,
+            F => 13,
+            G => 14,
+            HH => 15,
+            IH(_) => 16,
+            IY(_) => 17,
+            JH => 18,
+            K => 19,
+            L => 20,
+            M => 21,
+            N => 22,
+            NG => 23,
+            OW(_) => 24,
+            OY(_) => 25,
+            P => 26,
+            R => 27,
+            S => 28,
+            SH => 29,
+            T => 30,
+            TH => 31,
+            UH(_) => 32,
+            UW(_) => 33,
+            V => 34,
+            W => 35,
+            Y => 36,
+            Z => 37,
+            ZH => 38,
+        }
+    }
 }
 
 macro_rules! parse_stress {
@@ -447,7 +545,7 @@ impl FromStr for Symbol {
 }
 
 /// Represents the complete pronunciation of a single word in the database
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rule {
     label: String,
@@ -457,6 +555,7 @@ pub struct Rule {
 
 impl Rule {
     #[doc(hidden)]
+    #[allow(clippy::redundant_field_names)]
     pub fn new<I: Into<String>>(label: I, pronunciation: Vec<Symbol>) -> Rule {
         Rule {
             label: label.into(),
@@ -483,6 +582,7 @@ impl Rule {
 impl FromStr for Rule {
     type Err = ParseError;
 
+    #[allow(clippy::redundant_closure)]
     fn from_str(s: &str) -> ParseResult<Rule> {
         let mut iter = s.split_whitespace().filter(|s| !s.is_empty());
         let label = iter.next().ok_or(ParseError::UnexpectedEOF("label"))?;
@@ -515,6 +615,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_vec() {
         let v = vec!["AA1", "K", "L", "TH"];
         let converted = v.iter().map(|s| Symbol::from_str(s).unwrap()).collect::<Vec<_>>();
@@ -0,0 +1,469 @@
+//! Rewriting pronunciations into other dialects
+//!
+//! The dictionary is written in General American. A `Dialect` is a list of rewrite rules
+//! applied to a pronunciation one after another, each rule written the way phonologists
+//! write them:
+//!
+//! ```text
+//! R -> / [vowel] _ $
+//! AO -> AA / _ [^R]
+//! ```
+//!
+//! The symbols before `->` are replaced by those after it, which may be none at all, when
+//! the symbols around them match the contexts either side of the `_` after the `/`. The
+//! context can be left out to make the change everywhere. Targets and contexts are made of
+//! the items of a `Pattern` that match a single symbol, along with `^` and `$`, or `#`, for
+//! the start and end of the word; they can't repeat or have alternatives. A replacement vowel
+//! written without a stress takes the stress of the vowel it replaces, so `AO -> AA` turns
+//! `AO1` into `AA1`.
+//!
+//! A rule looks for its contexts in the pronunciation as it was before the rule, and makes
+//! every change it can from left to right without changing the same symbol twice.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{Error, ParseError, ParseResult, Result};
+use crate::pattern::{sequence, Item, Matcher};
+use crate::Cmudict;
+
+// R isn't said unless a vowel comes after it, and unstressed ER becomes a schwa
+const NON_RHOTIC: &str = "
+R -> / [vowel] _ [consonant]
+R -> / [vowel] _ $
+ER0 -> AH0
+";
+
+// AO becomes AA, except before R where the vowels stay apart
+const COT_CAUGHT: &str = "
+AO -> AA / _ [^R]
+AO -> AA / _ $
+";
+
+// T between vowels, or after R, becomes a D-like flap when the vowel after it is unstressed
+const FLAPPING: &str = "
+T -> D / [vowel R] _ [AA0 AE0 AH0 AO0 AW0 AY0 EH0 ER0 EY0 IH0 IY0 OW0 OY0 UH0 UW0]
+";
+
+// Y isn't said between a coronal consonant and a stressed UW, as in "new"
+const YOD_DROPPING: &str = "
+Y -> / [T D N S Z L TH] _ [UW1 UW2]
+";
+
+/// A rule that rewrites some symbols of a pronunciation when they are in the right context
+///
+/// Rules are written as `target -> replacement / left _ right`, as described in the
+/// documentation of `Dialect`.
+#[derive(Debug, Clone)]
+pub struct Rewrite {
+    source: String,
+    target: Vec<Matcher>,
+    replacement: Vec<Matcher>,
+    left: Vec<Item>,
+    right: Vec<Item>,
+}
+
+impl Rewrite {
+    /// Compiles a rewrite rule
+    pub fn new(rule: &str) -> ParseResult<Rewrite> {
+        let invalid = |why: &str| invalid_rule(rule, why);
+        let RuleText { target, replacement, left, right } = RuleText::new(rule)?;
+        let symbols = |items: Vec<Item>| -> ParseResult<Vec<Matcher>> {
+            items
+                .into_iter()
+                .map(|item| match item {
+                    Item::Symbol(matcher) => Ok(matcher),
+                    _ => Err(invalid("has a word boundary outside of its context")),
+                })
+                .collect()
+        };
+        let target = symbols(sequence(target)?)?;
+        if target.is_empty() {
+            return Err(invalid("has nothing to replace"));
+        }
+        let replacement = symbols(sequence(replacement)?)?;
+        if replacement.iter().any(|m| !matches!(m, Matcher::Exact(_) | Matcher::Base(_))) {
+            return Err(invalid("has something other than symbols in its replacement"));
+        }
+        Ok(Rewrite {
+            source: rule.trim().to_string(),
+            target,
+            replacement,
+            left,
+            right,
+        })
+    }
+
+    /// Applies the rule everywhere it matches in a pronunciation
+    pub fn apply(&self, phones: &[Symbol]) -> Vec<Symbol> {
+        let mut rewritten = Vec::with_capacity(phones.len());
+        let mut pos = 0;
+        while pos < phones.len() {
+            if !self.matches(phones, pos) {
+                rewritten.push(phones[pos]);
+                pos += 1;
+                continue;
+            }
+            let matched = &phones[pos..pos + self.target.len()];
+            for (idx, matcher) in self.replacement.iter().enumerate() {
+                rewritten.push(match *matcher {
+                    Matcher::Base(symbol) => symbol.with_stress(stress(matched, idx)),
+                    Matcher::Exact(symbol) => symbol,
+                    _ => unreachable!("replacements are only made of symbols"),
+                });
+            }
+            pos += self.target.len();
+        }
+        rewritten
+    }
+
+    fn matches(&self, phones: &[Symbol], pos: usize) -> bool {
+        let end = pos + self.target.len();
+        end <= phones.len()
+            && self.target.iter().zip(&phones[pos..end]).all(|(m, symbol)| m.matches(symbol))
+            && before(&self.left, phones, pos)
+            && after(&self.right, phones, end)
+    }
+}
+
+// the parts of a rule written as `target -> replacement / left _ right`, with the target and
+// replacement left for the rule to parse
+pub(crate) struct RuleText<'a> {
+    pub(crate) target: &'a str,
+    pub(crate) replacement: &'a str,
+    pub(crate) left: Vec<Item>,
+    pub(crate) right: Vec<Item>,
+}
+
+impl<'a> RuleText<'a> {
+    pub(crate) fn new(rule: &'a str) -> ParseResult<RuleText<'a>> {
+        let (change, context) = match rule.split_once('/') {
+            Some((change, context)) => (change, Some(context)),
+            None => (rule, None),
+        };
+        let (target, replacement) =
+            change.split_once("->").ok_or_else(|| invalid_rule(rule, "has no ->"))?;
+        let (left, right) = match context {
+            Some(context) => {
+                let (left, right) =
+                    context.split_once('_').ok_or_else(|| invalid_rule(rule, "has no _"))?;
+                (sequence(left)?, sequence(right)?)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        Ok(RuleText {
+            target,
+            replacement,
+            left,
+            right,
+        })
+    }
+}
+
+// the error for a rule that can't be compiled, and why
+pub(crate) fn invalid_rule(rule: &str, why: &str) -> ParseError {
+    ParseError::InvalidRewrite(format!("{} {}", rule.trim(), why))
+}
+
+// the stress of the vowel a replacement vowel takes the place of: the one at the same place in
+// the target if there is one, or else the first vowel of the target
+pub(crate) fn stress(matched: &[Symbol], idx: usize) -> Stress {
+    matched
+        .get(idx)
+        .filter(|symbol| symbol.is_syllable())
+        .or_else(|| matched.iter().find(|symbol| symbol.is_syllable()))
+        .and_then(Symbol::stress)
+        .unwrap_or(Stress::None)
+}
+
+// whether a left context matches the symbols that end at `pos`
+fn before(items: &[Item], phones: &[Symbol], mut pos: usize) -> bool {
+    for item in items.iter().rev() {
+        match item {
+            Item::Start | Item::Boundary if pos != 0 => return false,
+            Item::End if pos != phones.len() => return false,
+            Item::Symbol(matcher) => {
+                if pos == 0 || !matcher.matches(&phones[pos - 1]) {
+                    return false;
+                }
+                pos -= 1;
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+// whether a right context matches the symbols that start at `pos`
+fn after(items: &[Item], phones: &[Symbol], mut pos: usize) -> bool {
+    for item in items {
+        match item {
+            Item::Start if pos != 0 => return false,
+            Item::End | Item::Boundary if pos != phones.len() => return false,
+            Item::Symbol(matcher) => {
+                if pos == phones.len() || !matcher.matches(&phones[pos]) {
+                    return false;
+                }
+                pos += 1;
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+impl FromStr for Rewrite {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<Rewrite> {
+        Rewrite::new(s)
+    }
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// A list of rewrite rules that turn General American pronunciations into another dialect's
+///
+/// Rules can be read from text with one rule per line. Blank lines and lines starting with
+/// `;;` are skipped. The built-in dialects can be chained with `Dialect::then`.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, Dialect, Stress, Symbol};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let water = dict.get("water").unwrap()[0].pronunciation();
+///
+/// let british = Dialect::non_rhotic();
+/// assert_eq!(
+///     british.apply(water),
+///     [Symbol::W, Symbol::AO(Stress::Primary), Symbol::T, Symbol::AH(Stress::None)]
+/// );
+///
+/// let western = Dialect::cot_caught().then(Dialect::flapping());
+/// assert_eq!(
+///     western.apply(water),
+///     [Symbol::W, Symbol::AA(Stress::Primary), Symbol::D, Symbol::ER(Stress::None)]
+/// );
+///
+/// let custom: Dialect = "
+/// ;; the vowel of \"cat\" is raised before nasals
+/// AE -> EH / _ [nasal]
+/// ".parse()?;
+/// let ham = dict.get("ham").unwrap()[0].pronunciation();
+/// assert_eq!(custom.apply(ham)[1], Symbol::EH(Stress::Primary));
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Dialect {
+    rewrites: Vec<Rewrite>,
+}
+
+impl Dialect {
+    /// Creates a dialect with no rules, which leaves pronunciations as they are
+    pub fn new() -> Dialect {
+        Dialect::default()
+    }
+
+    /// Creates a non-rhotic dialect, like British Received Pronunciation, where `R` is only
+    /// said before a vowel and unstressed `ER` becomes a schwa
+    pub fn non_rhotic() -> Dialect {
+        builtin(NON_RHOTIC)
+    }
+
+    /// Creates a dialect where "cot" and "caught" sound the same, with `AO` merged into `AA`
+    /// everywhere but before `R`
+    pub fn cot_caught() -> Dialect {
+        builtin(COT_CAUGHT)
+    }
+
+    /// Creates a dialect where `T` is flapped to `D` between a vowel and an unstressed vowel,
+    /// as in "butter"
+    pub fn flapping() -> Dialect {
+        builtin(FLAPPING)
+    }
+
+    /// Creates a dialect where `Y` is dropped between a coronal consonant and a stressed `UW`,
+    /// so "anew" is only ever said without it
+    pub fn yod_dropping() -> Dialect {
+        builtin(YOD_DROPPING)
+    }
+
+    /// Loads a dialect's rules from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Dialect> {
+        let file = File::open(path)?;
+        Dialect::read(BufReader::new(file))
+    }
+
+    fn read<R: BufRead>(reader: R) -> Result<Dialect> {
+        let mut dialect = Dialect::new();
+        for line in crate::entry_lines(reader) {
+            let (_, line) = line?;
+            dialect.push(line.parse()?);
+        }
+        Ok(dialect)
+    }
+
+    /// Adds a rule, to be applied after the rules already in the dialect
+    pub fn push(&mut self, rewrite: Rewrite) {
+        self.rewrites.push(rewrite);
+    }
+
+    /// Adds the rules of another dialect after this one's
+    pub fn then(mut self, other: Dialect) -> Dialect {
+        self.rewrites.extend(other.rewrites);
+        self
+    }
+
+    /// Returns the rules of the dialect, in the order they are applied
+    pub fn rewrites(&self) -> &[Rewrite] {
+        &self.rewrites
+    }
+
+    /// Returns the number of rules in the dialect
+    pub fn len(&self) -> usize {
+        self.rewrites.len()
+    }
+
+    /// Returns `true` if the dialect has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rewrites.is_empty()
+    }
+
+    /// Rewrites a pronunciation with every rule, one after another
+    pub fn apply(&self, phones: &[Symbol]) -> Vec<Symbol> {
+        let mut phones = phones.to_vec();
+        for rewrite in &self.rewrites {
+            phones = rewrite.apply(&phones);
+        }
+        phones
+    }
+}
+
+fn builtin(rules: &str) -> Dialect {
+    rules.parse().expect("built-in rules are valid")
+}
+
+impl FromStr for Dialect {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Dialect> {
+        Dialect::read(Cursor::new(s))
+    }
+}
+
+impl Cmudict {
+    /// Rewrites every pronunciation in the dictionary into a dialect
+    ///
+    /// Pronunciations of a word that become the same are only kept once, with the label of
+    /// the first of them.
+    pub fn to_dialect(&self, dialect: &Dialect) -> Cmudict {
+        let map = self
+            .map
+            .iter()
+            .map(|(word, rules)| {
+                let mut rewritten: Vec<Rule> = Vec::with_capacity(rules.len());
+                for rule in rules {
+                    let phones = dialect.apply(rule.pronunciation());
+                    if !rewritten.iter().any(|r| r.pronunciation() == &phones[..]) {
+                        rewritten.push(Rule::new(rule.label(), phones));
+                    }
+                }
+                (word.clone(), rewritten)
+            })
+            .collect();
+        Cmudict::from_map(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phones(s: &str) -> Vec<Symbol> {
+        s.split_whitespace().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn builtins() {
+        let rewrite = |dialect: &Dialect, from: &str| dialect.apply(&phones(from));
+        let british = Dialect::non_rhotic();
+        assert_eq!(rewrite(&british, "K AA1 R"), phones("K AA1"));
+        assert_eq!(rewrite(&british, "HH AO1 R S"), phones("HH AO1 S"));
+        assert_eq!(rewrite(&british, "K AE1 R IY0"), phones("K AE1 R IY0"));
+        assert_eq!(rewrite(&british, "B ER1 D"), phones("B ER1 D"));
+
+        let merged = Dialect::cot_caught();
+        assert_eq!(rewrite(&merged, "TH AO1 T"), phones("TH AA1 T"));
+        assert_eq!(rewrite(&merged, "L AO1"), phones("L AA1"));
+        assert_eq!(rewrite(&merged, "N AO1 R TH"), phones("N AO1 R TH"));
+
+        let flapping = Dialect::flapping();
+        assert_eq!(rewrite(&flapping, "S IH1 T IY0"), phones("S IH1 D IY0"));
+        assert_eq!(rewrite(&flapping, "AH0 T AE1 K"), phones("AH0 T AE1 K"));
+
+        let yod = Dialect::yod_dropping();
+        assert_eq!(rewrite(&yod, "AH0 N Y UW1"), phones("AH0 N UW1"));
+        assert_eq!(rewrite(&yod, "V AE1 L Y UW0"), phones("V AE1 L Y UW0"));
+
+        assert_eq!(Dialect::new().apply(&phones("K AA1 R")), phones("K AA1 R"));
+        assert_eq!(british.then(merged).len(), 5);
+    }
+
+    #[test]
+    fn rewrites() {
+        let rule: Rewrite = "T R -> CH R / ^ _".parse().unwrap();
+        assert_eq!(rule.apply(&phones("T R IY1")), phones("CH R IY1"));
+        assert_eq!(rule.apply(&phones("P AE1 T R IY0")), phones("P AE1 T R IY0"));
+        assert_eq!(rule.to_string(), "T R -> CH R / ^ _");
+
+        // replacements take the stress of the vowels they replace, unless they have their own
+        let rule: Rewrite = "EY -> EH IY".parse().unwrap();
+        assert_eq!(rule.apply(&phones("D EY1 T EY2")), phones("D EH1 IY1 T EH2 IY2"));
+        let rule: Rewrite = "AH0 -> AH1".parse().unwrap();
+        assert_eq!(rule.apply(&phones("AH0 B AH1")), phones("AH1 B AH1"));
+
+        // contexts are read from the pronunciation before the rule makes any changes
+        let rule: Rewrite = "AH -> / AH _".parse().unwrap();
+        assert_eq!(rule.apply(&phones("AH0 AH0 AH0")), phones("AH0"));
+
+        assert!("AO AA".parse::<Rewrite>().is_err());
+        assert!("-> AA".parse::<Rewrite>().is_err());
+        assert!("AO -> [vowel]".parse::<Rewrite>().is_err());
+        assert!("AO -> AA / R".parse::<Rewrite>().is_err());
+        assert!("AO -> AA / R* _".parse::<Rewrite>().is_err());
+        assert!("AO $ -> AA".parse::<Rewrite>().is_err());
+        assert!("ZZ -> AA".parse::<Rewrite>().is_err());
+        assert!(";; nothing\n\nAO -> AA\nAO ->\n".parse::<Dialect>().is_ok());
+        assert!("AO -> AA\nAO\n".parse::<Dialect>().is_err());
+    }
+
+    #[test]
+    fn dictionaries() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        assert_eq!(d.get("anew").unwrap().len(), 2);
+        let dropped = d.to_dialect(&Dialect::yod_dropping());
+        assert_eq!(dropped.len(), d.len());
+        assert_eq!(dropped.get("anew").unwrap(), &[Rule::new("anew", phones("AH0 N UW1"))]);
+    }
+}
@@ -0,0 +1,261 @@
+//! Weighted edit distance between pronunciations
+//!
+//! Substituting one symbol for another costs less the more articulatory features they share,
+//! so `P` for `B`, which differ only in voicing, is much cheaper than `P` for `IY`.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Stress, Symbol};
+use crate::features::{Features, Voicing};
+
+/// Weights for the edit distance between two pronunciations
+///
+/// Every cost is between zero and one by default, with substitutions between a vowel and a
+/// consonant costing the same as an insertion or a deletion.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PhoneticDistance {
+    /// The cost of a symbol in the second pronunciation that isn't in the first
+    pub insertion: f64,
+    /// The cost of a symbol in the first pronunciation that isn't in the second
+    pub deletion: f64,
+    /// The cost of substituting a vowel for a consonant, or a consonant for a vowel
+    pub vowel_consonant: f64,
+    /// The cost of a vowel with the wrong stress, on top of any difference in quality
+    pub stress: f64,
+    /// Treat vowels that only differ in stress as the same
+    pub ignore_stress: bool,
+}
+
+impl Default for PhoneticDistance {
+    fn default() -> PhoneticDistance {
+        PhoneticDistance {
+            insertion: 1.0,
+            deletion: 1.0,
+            vowel_consonant: 1.0,
+            stress: 0.1,
+            ignore_stress: false,
+        }
+    }
+}
+
+/// A single step of an alignment between two pronunciations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Edit {
+    /// The same symbol is in both pronunciations
+    Match(Symbol),
+    /// A symbol of the first pronunciation is replaced by one of the second
+    Substitute(Symbol, Symbol),
+    /// A symbol is only in the second pronunciation
+    Insert(Symbol),
+    /// A symbol is only in the first pronunciation
+    Delete(Symbol),
+}
+
+/// The cheapest way of turning one pronunciation into another
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Alignment {
+    distance: f64,
+    edits: Vec<Edit>,
+}
+
+impl Alignment {
+    /// Returns the total cost of the edits
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Returns the edits in order, from the start of both pronunciations
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+}
+
+impl PhoneticDistance {
+    /// Returns the cost of substituting one symbol for another
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{PhoneticDistance, Symbol, Stress};
+    ///
+    /// let weights = PhoneticDistance::default();
+    /// let voicing = weights.substitution(Symbol::P, Symbol::B);
+    /// let vowel = weights.substitution(Symbol::P, Symbol::IY(Stress::None));
+    /// assert!(voicing < vowel);
+    /// ```
+    pub fn substitution(&self, a: Symbol, b: Symbol) -> f64 {
+        let stress = match (a.stress(), b.stress()) {
+            (Some(x), Some(y)) if x != y && !self.ignore_stress => self.stress,
+            _ => 0.0,
+        };
+        if a.with_stress(Stress::None) == b.with_stress(Stress::None) {
+            return stress;
+        }
+        match (a.features(), b.features()) {
+            (Features::Consonant(x), Features::Consonant(y)) => {
+                let voicing = if x.voicing == y.voicing { 0.0 } else { 0.25 };
+                let places = (x.place as i32 - y.place as i32).abs().min(3);
+                let manner = if x.manner == y.manner { 0.0 } else { 0.45 };
+                voicing + 0.1 * f64::from(places) + manner
+            }
+            (Features::Vowel(x), Features::Vowel(y)) => {
+                let height = f64::from((x.height as i32 - y.height as i32).abs()) / 6.0;
+                let backness = f64::from((x.backness as i32 - y.backness as i32).abs()) / 2.0;
+                let flag = |p: bool, q: bool| if p == q { 0.0 } else { 1.0 };
+                0.3 * height
+                    + 0.2 * backness
+                    + 0.1 * flag(x.rounded, y.rounded)
+                    + 0.2 * flag(x.rhotic, y.rhotic)
+                    + 0.1 * flag(x.diphthong, y.diphthong)
+                    + stress
+            }
+            (Features::Consonant(c), Features::Vowel(_))
+            | (Features::Vowel(_), Features::Consonant(c)) => {
+                // voiced consonants are a little closer to vowels
+                if c.voicing == Voicing::Voiced {
+                    self.vowel_consonant * 0.9
+                } else {
+                    self.vowel_consonant
+                }
+            }
+        }
+    }
+
+    /// Returns the cost of turning one pronunciation into another
+    pub fn distance(&self, a: &[Symbol], b: &[Symbol]) -> f64 {
+        let mut row: Vec<f64> = (0..=b.len()).map(|j| j as f64 * self.insertion).collect();
+        for (i, &x) in a.iter().enumerate() {
+            let mut diagonal = row[0];
+            row[0] = (i + 1) as f64 * self.deletion;
+            for (j, &y) in b.iter().enumerate() {
+                let cost = (diagonal + self.substitution(x, y))
+                    .min(row[j + 1] + self.deletion)
+                    .min(row[j] + self.insertion);
+                diagonal = row[j + 1];
+                row[j + 1] = cost;
+            }
+        }
+        row[b.len()]
+    }
+
+    /// Finds the cheapest alignment of two pronunciations
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Edit, PhoneticDistance, Symbol, Stress};
+    ///
+    /// let cat = [Symbol::K, Symbol::AE(Stress::Primary), Symbol::T];
+    /// let gas = [Symbol::G, Symbol::AE(Stress::Primary), Symbol::S];
+    /// let alignment = PhoneticDistance::default().align(&cat, &gas);
+    /// assert_eq!(
+    ///     alignment.edits(),
+    ///     &[
+    ///         Edit::Substitute(Symbol::K, Symbol::G),
+    ///         Edit::Match(Symbol::AE(Stress::Primary)),
+    ///         Edit::Substitute(Symbol::T, Symbol::S),
+    ///     ]
+    /// );
+    /// ```
+    pub fn align(&self, a: &[Symbol], b: &[Symbol]) -> Alignment {
+        let (n, m) = (a.len(), b.len());
+        let mut cost = vec![vec![0.0; m + 1]; n + 1];
+        for (j, c) in cost[0].iter_mut().enumerate() {
+            *c = j as f64 * self.insertion;
+        }
+        for i in 1..=n {
+            cost[i][0] = i as f64 * self.deletion;
+            for j in 1..=m {
+                cost[i][j] = (cost[i - 1][j - 1] + self.substitution(a[i - 1], b[j - 1]))
+                    .min(cost[i - 1][j] + self.deletion)
+                    .min(cost[i][j - 1] + self.insertion);
+            }
+        }
+
+        let mut edits = Vec::with_capacity(n.max(m));
+        let (mut i, mut j) = (n, m);
+        while i > 0 || j > 0 {
+            if i > 0 && j > 0 {
+                let sub = self.substitution(a[i - 1], b[j - 1]);
+                if close(cost[i][j], cost[i - 1][j - 1] + sub) {
+                    edits.push(if a[i - 1] == b[j - 1] {
+                        Edit::Match(a[i - 1])
+                    } else {
+                        Edit::Substitute(a[i - 1], b[j - 1])
+                    });
+                    i -= 1;
+                    j -= 1;
+                    continue;
+                }
+            }
+            if i > 0 && close(cost[i][j], cost[i - 1][j] + self.deletion) {
+                edits.push(Edit::Delete(a[i - 1]));
+                i -= 1;
+            } else {
+                edits.push(Edit::Insert(b[j - 1]));
+                j -= 1;
+            }
+        }
+        edits.reverse();
+        Alignment {
+            distance: cost[n][m],
+            edits,
+        }
+    }
+}
+
+fn close(x: f64, y: f64) -> bool {
+    (x - y).abs() < 1e-9
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+    use crate::Cmudict;
+
+    #[test]
+    fn substituting() {
+        let weights = PhoneticDistance::default();
+        assert_eq!(weights.substitution(T, T), 0.0);
+        assert!(weights.substitution(P, B) < weights.substitution(P, K));
+        assert!(weights.substitution(P, K) < weights.substitution(P, M));
+        let iy = IY(Stress::None);
+        let ih = weights.substitution(iy, IH(Stress::None));
+        let aa = weights.substitution(iy, AA(Stress::None));
+        assert!(ih < aa);
+        assert_eq!(weights.substitution(AH(Stress::Primary), AH(Stress::None)), 0.1);
+
+        let ignoring = PhoneticDistance {
+            ignore_stress: true,
+            ..PhoneticDistance::default()
+        };
+        assert_eq!(ignoring.substitution(AH(Stress::Primary), AH(Stress::None)), 0.0);
+    }
+
+    #[test]
+    fn aligning() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let weights = PhoneticDistance::default();
+        let p = |word: &str| d.get(word).unwrap()[0].pronunciation();
+
+        // "cats" only adds an S to "cat"
+        let alignment = weights.align(p("cat"), p("cats"));
+        assert_eq!(alignment.distance(), 1.0);
+        assert_eq!(alignment.edits().last(), Some(&Edit::Insert(S)));
+        assert_eq!(weights.distance(p("cat"), p("cats")), 1.0);
+
+        assert!(weights.distance(p("bat"), p("pat")) < weights.distance(p("bat"), p("sat")));
+        for (a, b) in &[("apple", "chapel"), ("strength", "length"), ("a", "cat")] {
+            assert!(close(weights.distance(p(a), p(b)), weights.align(p(a), p(b)).distance()));
+            assert!(close(weights.distance(p(a), p(b)), weights.distance(p(b), p(a))));
+        }
+        assert_eq!(weights.align(&[], &[]).edits(), &[]);
+    }
+}
@@ -10,6 +10,9 @@ pub enum Error {
     #[error("rule parse error: {0}")]
     /// Wraps errors coming from parsing rules with cmudict_core
     RuleParseError(#[from] ParseError),
+    /// A saved model couldn't be understood
+    #[error("line {0} of the model is malformed")]
+    InvalidModel(usize),
 }
 
 /// Shortcut for Result<T, errors::Error>
@@ -33,6 +36,15 @@ pub enum ParseError {
     /// Invalid character after start of phoneme
     #[error("Expected {0} after {1}, got {2}")]
     UnexpectedCharacterAfter(&'static str, &'static str, char),
+    /// A phone label that isn't part of the extended phone set
+    #[error("Unknown phone {0}")]
+    UnknownPhone(String),
+    /// A search pattern that can't be compiled
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    /// A rewrite rule that can't be understood
+    #[error("Invalid rewrite rule: {0}")]
+    InvalidRewrite(String),
 }
 
 /// Shortcut for Result<T, errors::ParseError>
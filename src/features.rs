@@ -0,0 +1,309 @@
+//! Articulatory features of each symbol
+//!
+//! Consonants are described by their voicing, place and manner of articulation, and vowels by
+//! the height and backness of the tongue, whether the lips are rounded, whether the vowel is
+//! r-coloured, and whether it glides into another vowel. The values are those of a broad
+//! transcription of General American English, the same one `Symbol::to_ipa` writes.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::Symbol;
+
+/// The articulatory features of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Features {
+    /// The features of a vowel
+    Vowel(VowelFeatures),
+    /// The features of a consonant
+    Consonant(ConsonantFeatures),
+}
+
+/// How a vowel is articulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VowelFeatures {
+    /// How high the tongue is, at the start of a diphthong
+    pub height: Height,
+    /// How far back the tongue is, at the start of a diphthong
+    pub backness: Backness,
+    /// Whether the lips are rounded
+    pub rounded: bool,
+    /// Whether the vowel is r-coloured
+    pub rhotic: bool,
+    /// Whether the vowel glides into another, like `AY` does
+    pub diphthong: bool,
+}
+
+/// How a consonant is articulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConsonantFeatures {
+    /// Whether the vocal folds vibrate
+    pub voicing: Voicing,
+    /// Where the airflow is obstructed
+    pub place: Place,
+    /// How the airflow is obstructed
+    pub manner: Manner,
+}
+
+/// Whether the vocal folds vibrate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Voicing {
+    Voiced,
+    Voiceless,
+}
+
+/// The place of articulation of a consonant, from the lips back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Place {
+    Bilabial,
+    /// Both the lips and the soft palate, as in `W`
+    LabialVelar,
+    Labiodental,
+    Dental,
+    Alveolar,
+    Postalveolar,
+    Palatal,
+    Velar,
+    Glottal,
+}
+
+/// The manner of articulation of a consonant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Manner {
+    Stop,
+    Affricate,
+    Fricative,
+    Nasal,
+    /// `L` and `R`
+    Liquid,
+    /// `W` and `Y`
+    Glide,
+}
+
+/// The height of a vowel, from close to open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Height {
+    Close,
+    NearClose,
+    CloseMid,
+    Mid,
+    OpenMid,
+    NearOpen,
+    Open,
+}
+
+/// The backness of a vowel, from front to back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Backness {
+    Front,
+    Central,
+    Back,
+}
+
+impl Symbol {
+    /// Returns the articulatory features of the symbol
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Features, Manner, Place, Symbol, Voicing};
+    ///
+    /// match Symbol::B.features() {
+    ///     Features::Consonant(b) => {
+    ///         assert_eq!(b.voicing, Voicing::Voiced);
+    ///         assert_eq!(b.place, Place::Bilabial);
+    ///         assert_eq!(b.manner, Manner::Stop);
+    ///     }
+    ///     Features::Vowel(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn features(&self) -> Features {
+        use self::Backness::*;
+        use self::Height::*;
+        use self::Manner::*;
+        use self::Place::*;
+        use self::Voicing::*;
+        use crate::core::Symbol::*;
+        let vowel = |height, backness, rounded, rhotic, diphthong| {
+            Features::Vowel(VowelFeatures {
+                height,
+                backness,
+                rounded,
+                rhotic,
+                diphthong,
+            })
+        };
+        let consonant = |voicing, place, manner| {
+            Features::Consonant(ConsonantFeatures {
+                voicing,
+                place,
+                manner,
+            })
+        };
+        match *self {
+            AA(_) => vowel(Open, Back, false, false, false),
+            AE(_) => vowel(NearOpen, Front, false, false, false),
+            AH(_) => vowel(OpenMid, Central, false, false, false),
+            AO(_) => vowel(OpenMid, Back, true, false, false),
+            AW(_) => vowel(Open, Front, false, false, true),
+            AY(_) => vowel(Open, Central, false, false, true),
+            EH(_) => vowel(OpenMid, Front, false, false, false),
+            ER(_) => vowel(Mid, Central, false, true, false),
+            EY(_) => vowel(CloseMid, Front, false, false, true),
+            IH(_) => vowel(NearClose, Front, false, false, false),
+            IY(_) => vowel(Close, Front, false, false, false),
+            OW(_) => vowel(CloseMid, Back, true, false, true),
+            OY(_) => vowel(OpenMid, Back, true, false, true),
+            UH(_) => vowel(NearClose, Back, true, false, false),
+            UW(_) => vowel(Close, Back, true, false, false),
+            B => consonant(Voiced, Bilabial, Stop),
+            CH => consonant(Voiceless, Postalveolar, Affricate),
+            D => consonant(Voiced, Alveolar, Stop),
+            DH => consonant(Voiced, Dental, Fricative),
+            F => consonant(Voiceless, Labiodental, Fricative),
+            G => consonant(Voiced, Velar, Stop),
+            HH => consonant(Voiceless, Glottal, Fricative),
+            JH => consonant(Voiced, Postalveolar, Affricate),
+            K => consonant(Voiceless, Velar, Stop),
+            L => consonant(Voiced, Alveolar, Liquid),
+            M => consonant(Voiced, Bilabial, Nasal),
+            N => consonant(Voiced, Alveolar, Nasal),
+            NG => consonant(Voiced, Velar, Nasal),
+            P => consonant(Voiceless, Bilabial, Stop),
+            R => consonant(Voiced, Postalveolar, Liquid),
+            S => consonant(Voiceless, Alveolar, Fricative),
+            SH => consonant(Voiceless, Postalveolar, Fricative),
+            T => consonant(Voiceless, Alveolar, Stop),
+            TH => consonant(Voiceless, Dental, Fricative),
+            V => consonant(Voiced, Labiodental, Fricative),
+            W => consonant(Voiced, LabialVelar, Glide),
+            Y => consonant(Voiced, Palatal, Glide),
+            Z => consonant(Voiced, Alveolar, Fricative),
+            ZH => consonant(Voiced, Postalveolar, Fricative),
+        }
+    }
+
+    /// Returns the manner of articulation of a consonant, or `None` for a vowel
+    pub fn manner(&self) -> Option<Manner> {
+        match self.features() {
+            Features::Consonant(features) => Some(features.manner),
+            Features::Vowel(_) => None,
+        }
+    }
+
+    /// Returns `true` if the symbol is a vowel
+    pub fn is_vowel(&self) -> bool {
+        self.is_syllable()
+    }
+
+    /// Returns `true` if the symbol is a consonant
+    pub fn is_consonant(&self) -> bool {
+        !self.is_syllable()
+    }
+
+    /// Returns `true` if the vocal folds vibrate, as they do for every vowel
+    pub fn is_voiced(&self) -> bool {
+        match self.features() {
+            Features::Consonant(features) => features.voicing == Voicing::Voiced,
+            Features::Vowel(_) => true,
+        }
+    }
+
+    /// Returns `true` for the stops `P`, `B`, `T`, `D`, `K` and `G`
+    pub fn is_stop(&self) -> bool {
+        self.manner() == Some(Manner::Stop)
+    }
+
+    /// Returns `true` for the affricates `CH` and `JH`
+    pub fn is_affricate(&self) -> bool {
+        self.manner() == Some(Manner::Affricate)
+    }
+
+    /// Returns `true` for the fricatives, including `HH`
+    pub fn is_fricative(&self) -> bool {
+        self.manner() == Some(Manner::Fricative)
+    }
+
+    /// Returns `true` for the nasals `M`, `N` and `NG`
+    pub fn is_nasal(&self) -> bool {
+        self.manner() == Some(Manner::Nasal)
+    }
+
+    /// Returns `true` for the liquids `L` and `R`
+    pub fn is_liquid(&self) -> bool {
+        self.manner() == Some(Manner::Liquid)
+    }
+
+    /// Returns `true` for the glides `W` and `Y`
+    pub fn is_glide(&self) -> bool {
+        self.manner() == Some(Manner::Glide)
+    }
+
+    /// Returns `true` for vowels, nasals, liquids and glides
+    pub fn is_sonorant(&self) -> bool {
+        !matches!(
+            self.manner(),
+            Some(Manner::Stop) | Some(Manner::Affricate) | Some(Manner::Fricative)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::alphabet::ALL;
+    use crate::core::Stress;
+    use crate::core::Symbol::*;
+
+    #[test]
+    fn categories() {
+        let count = |pred: fn(&Symbol) -> bool| ALL.iter().filter(|s| pred(s)).count();
+        assert_eq!(count(Symbol::is_vowel), 15);
+        assert_eq!(count(Symbol::is_consonant), 24);
+        assert_eq!(count(Symbol::is_stop), 6);
+        assert_eq!(count(Symbol::is_affricate), 2);
+        assert_eq!(count(Symbol::is_fricative), 9);
+        assert_eq!(count(Symbol::is_nasal), 3);
+        assert_eq!(count(Symbol::is_liquid), 2);
+        assert_eq!(count(Symbol::is_glide), 2);
+        assert_eq!(count(Symbol::is_sonorant), 22);
+
+        assert!(Symbol::Z.is_voiced());
+        assert!(!Symbol::S.is_voiced());
+        assert!(AE(Stress::None).is_voiced());
+    }
+
+    #[test]
+    fn features() {
+        // every symbol is told apart by its features
+        let unique: HashSet<Features> = ALL.iter().map(Symbol::features).collect();
+        assert_eq!(unique.len(), ALL.len());
+
+        match UW(Stress::Primary).features() {
+            Features::Vowel(uw) => {
+                assert_eq!(uw.height, Height::Close);
+                assert_eq!(uw.backness, Backness::Back);
+                assert!(uw.rounded);
+                assert!(!uw.diphthong);
+            }
+            Features::Consonant(_) => panic!("UW is a vowel"),
+        }
+        assert_eq!(OY(Stress::None).features(), OY(Stress::Primary).features());
+    }
+}
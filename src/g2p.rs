@@ -0,0 +1,905 @@
+//! Letter-to-sound prediction for words that aren't in the dictionary
+//!
+//! Two predictors are provided: `LetterRules`, a hand written set of English spelling rules in
+//! the style of the NRL text-to-speech rules, and `ContextModel`, which learns which sounds
+//! each letter makes in context from the entries of a loaded `Cmudict`.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Stress, Symbol};
+use crate::Cmudict;
+
+/// Something that can guess the pronunciation of a word
+pub trait Predictor {
+    /// Guesses the pronunciation of `word`
+    ///
+    /// Characters the predictor knows nothing about are skipped, so the result may be empty.
+    fn predict(&self, word: &str) -> Vec<Symbol>;
+}
+
+/* Rule based prediction */
+
+// (left context, letters, right context, phones)
+//
+// Contexts use the classic NRL notation:
+//   ' ' word boundary      '#' one or more vowels   ':' zero or more consonants
+//   '^' one consonant      '.' one voiced consonant '+' a front vowel (e, i, y)
+//   '%' a suffix (er, e, es, ed, ing, ely)          '&' a sibilant
+//   '@' a consonant that makes a following "u" sound like "oo"
+// Anything else has to match literally. Rules for each letter are tried in order.
+const ENGLISH_RULES: &[(&str, &str, &str, &str)] = &[
+    (" ", "a", " ", "AH"),
+    (" ", "are", " ", "AA R"),
+    (" ", "ar", "o", "AH R"),
+    ("", "ar", "#", "EH R"),
+    ("^", "as", "#", "EY S"),
+    ("", "a", "wa", "AH"),
+    ("", "aw", "", "AO"),
+    (" :", "any", "", "EH N IY"),
+    ("", "a", "^+#", "EY"),
+    ("#:", "ally", "", "AH L IY"),
+    (" ", "al", "#", "AH L"),
+    ("", "again", "", "AH G EH N"),
+    ("#:", "ag", "e", "IH JH"),
+    ("", "a", "^+:#", "AE"),
+    (" :", "a", "^+ ", "EY"),
+    ("", "a", "^%", "EY"),
+    (" ", "arr", "", "AH R"),
+    ("", "arr", "", "AE R"),
+    (" :", "ar", " ", "AA R"),
+    ("", "ar", " ", "ER"),
+    ("", "ar", "", "AA R"),
+    ("", "air", "", "EH R"),
+    ("", "ai", "", "EY"),
+    ("", "ay", "", "EY"),
+    ("", "au", "", "AO"),
+    ("#:", "al", " ", "AH L"),
+    ("#:", "als", " ", "AH L Z"),
+    ("", "alk", "", "AO K"),
+    ("", "al", "^", "AO L"),
+    (" :", "able", "", "EY B AH L"),
+    ("", "able", "", "AH B AH L"),
+    ("", "ang", "+", "EY N JH"),
+    ("", "a", "", "AE"),
+    (" ", "be", "^#", "B IH"),
+    ("", "being", "", "B IY IH NG"),
+    (" ", "both", " ", "B OW TH"),
+    (" ", "bus", "#", "B IH Z"),
+    ("", "buil", "", "B IH L"),
+    ("", "bb", "", "B"),
+    ("", "b", "", "B"),
+    (" ", "ch", "^", "K"),
+    ("^e", "ch", "", "K"),
+    ("", "ch", "", "CH"),
+    (" s", "ci", "#", "S AY"),
+    ("", "ci", "a", "SH"),
+    ("", "ci", "o", "SH"),
+    ("", "ci", "en", "SH"),
+    ("", "c", "+", "S"),
+    ("", "ck", "", "K"),
+    ("", "com", "%", "K AH M"),
+    ("", "cc", "+", "K S"),
+    ("", "cc", "", "K"),
+    ("", "c", "", "K"),
+    ("#:", "ded", " ", "D IH D"),
+    (".e", "d", " ", "D"),
+    ("#:^e", "d", " ", "T"),
+    (" ", "de", "^#", "D IH"),
+    (" ", "do", " ", "D UW"),
+    (" ", "does", "", "D AH Z"),
+    (" ", "doing", "", "D UW IH NG"),
+    (" ", "dow", "", "D AW"),
+    ("", "du", "a", "JH UW"),
+    ("", "dd", "", "D"),
+    ("", "d", "", "D"),
+    ("#:", "e", " ", ""),
+    (" :", "e", " ", "IY"),
+    ("#", "ed", " ", "D"),
+    ("#:", "e", "d ", ""),
+    ("", "ev", "er", "EH V"),
+    ("", "e", "^%", "IY"),
+    ("", "eri", "#", "IY R IY"),
+    ("", "eri", "", "EH R IH"),
+    ("#:", "er", "#", "ER"),
+    ("", "er", "#", "EH R"),
+    ("", "er", "", "ER"),
+    (" ", "even", "", "IY V EH N"),
+    ("#:", "e", "w", ""),
+    ("@", "ew", "", "UW"),
+    ("", "ew", "", "Y UW"),
+    ("", "e", "o", "IY"),
+    ("#:&", "es", " ", "IH Z"),
+    ("#:", "e", "s ", ""),
+    ("#:", "ely", " ", "L IY"),
+    ("#:", "ement", "", "M EH N T"),
+    ("", "eful", "", "F UH L"),
+    ("", "ee", "", "IY"),
+    ("", "earn", "", "ER N"),
+    (" ", "ear", "^", "ER"),
+    ("", "ead", "", "EH D"),
+    ("#:", "ea", " ", "IY AH"),
+    ("", "ea", "su", "EH"),
+    ("", "ea", "", "IY"),
+    ("", "eigh", "", "EY"),
+    ("", "ei", "", "IY"),
+    (" ", "eye", "", "AY"),
+    ("", "ey", "", "IY"),
+    ("", "eu", "", "Y UW"),
+    ("", "e", "", "EH"),
+    ("", "ful", "", "F UH L"),
+    ("", "ff", "", "F"),
+    ("", "f", "", "F"),
+    ("", "giv", "", "G IH V"),
+    (" ", "g", "i^", "G"),
+    ("", "ge", "t", "G EH"),
+    ("su", "gges", "", "G JH EH S"),
+    ("", "gg", "", "G"),
+    (" b#", "g", "", "G"),
+    ("", "g", "+", "JH"),
+    ("", "great", "", "G R EY T"),
+    ("#", "gh", "", ""),
+    ("", "g", "", "G"),
+    (" ", "hav", "", "HH AE V"),
+    (" ", "here", "", "HH IY R"),
+    (" ", "hour", "", "AW ER"),
+    ("", "how", "", "HH AW"),
+    ("", "h", "#", "HH"),
+    ("", "h", "", ""),
+    (" ", "in", "", "IH N"),
+    (" ", "i", " ", "AY"),
+    ("", "in", "d", "AY N"),
+    ("", "ier", "", "IY ER"),
+    ("#:r", "ied", "", "IY D"),
+    ("", "ied", " ", "AY D"),
+    ("", "ien", "", "IY EH N"),
+    ("", "ie", "t", "AY EH"),
+    (" :", "i", "%", "AY"),
+    ("", "i", "%", "IY"),
+    ("", "ie", "", "IY"),
+    ("", "i", "^+:#", "IH"),
+    ("", "ir", "#", "AY R"),
+    ("", "iz", "%", "AY Z"),
+    ("", "is", "%", "AY Z"),
+    ("", "i", "d%", "AY"),
+    ("+^", "i", "^+", "IH"),
+    ("", "i", "t%", "AY"),
+    ("#:^", "i", "^+", "IH"),
+    ("", "i", "^+", "AY"),
+    ("", "ir", "", "ER"),
+    ("", "igh", "", "AY"),
+    ("", "ild", "", "AY L D"),
+    ("", "ign", " ", "AY N"),
+    ("", "ign", "^", "AY N"),
+    ("", "ign", "%", "AY N"),
+    ("", "ique", "", "IY K"),
+    ("", "i", "", "IH"),
+    ("", "j", "", "JH"),
+    (" ", "k", "n", ""),
+    ("", "k", "", "K"),
+    ("", "lo", "c#", "L OW"),
+    ("l", "l", "", ""),
+    ("#:^", "l", "%", "AH L"),
+    ("", "lead", "", "L IY D"),
+    ("", "l", "", "L"),
+    ("", "mov", "", "M UW V"),
+    ("", "mm", "", "M"),
+    ("", "m", "", "M"),
+    ("e", "ng", "+", "N JH"),
+    ("", "ng", "r", "NG G"),
+    ("", "ng", "#", "NG G"),
+    ("", "ngl", "%", "NG G AH L"),
+    ("", "ng", "", "NG"),
+    ("", "nk", "", "NG K"),
+    (" ", "now", " ", "N AW"),
+    ("", "nn", "", "N"),
+    ("", "n", "", "N"),
+    ("", "of", " ", "AH V"),
+    ("", "orough", "", "ER OW"),
+    ("#:", "or", " ", "ER"),
+    ("#:", "ors", " ", "ER Z"),
+    ("", "or", "", "AO R"),
+    (" ", "one", "", "W AH N"),
+    ("", "ow", "", "OW"),
+    (" ", "over", "", "OW V ER"),
+    ("", "ov", "", "AH V"),
+    ("", "o", "^%", "OW"),
+    ("", "o", "^en", "OW"),
+    ("", "o", "^i#", "OW"),
+    ("", "ol", "d", "OW L"),
+    ("", "ought", "", "AO T"),
+    ("", "ough", "", "AH F"),
+    (" ", "ou", "", "AW"),
+    ("h", "ou", "s#", "AW"),
+    ("", "ous", "", "AH S"),
+    ("", "our", "", "AO R"),
+    ("", "ould", "", "UH D"),
+    ("^", "ou", "^l", "AH"),
+    ("", "oup", "", "UW P"),
+    ("", "ou", "", "AW"),
+    ("", "oy", "", "OY"),
+    ("", "oing", "", "OW IH NG"),
+    ("", "oi", "", "OY"),
+    ("", "oor", "", "AO R"),
+    ("", "ook", "", "UH K"),
+    ("", "ood", "", "UH D"),
+    ("", "oo", "", "UW"),
+    ("", "o", "e", "OW"),
+    ("", "o", " ", "OW"),
+    ("", "oa", "", "OW"),
+    (" ", "only", "", "OW N L IY"),
+    (" ", "once", "", "W AH N S"),
+    ("c", "o", "n", "AA"),
+    ("", "o", "ng", "AO"),
+    (" :^", "o", "n", "AH"),
+    ("i", "on", "", "AH N"),
+    ("#:", "on", " ", "AH N"),
+    ("#^", "on", "", "AH N"),
+    ("", "o", "st ", "OW"),
+    ("", "of", "^", "AO F"),
+    ("", "other", "", "AH DH ER"),
+    ("", "oss", " ", "AO S"),
+    ("#:^", "om", "", "AH M"),
+    ("", "o", "", "AA"),
+    ("", "ph", "", "F"),
+    ("", "peop", "", "P IY P"),
+    ("", "pow", "", "P AW"),
+    ("", "put", " ", "P UH T"),
+    ("", "pp", "", "P"),
+    ("", "p", "", "P"),
+    ("", "quar", "", "K W AO R"),
+    ("", "qu", "", "K W"),
+    ("", "q", "", "K"),
+    (" ", "re", "^#", "R IY"),
+    ("", "rr", "", "R"),
+    ("", "r", "", "R"),
+    ("", "sh", "", "SH"),
+    ("#", "sion", "", "ZH AH N"),
+    ("", "some", "", "S AH M"),
+    ("#", "sur", "#", "ZH ER"),
+    ("", "sur", "#", "SH ER"),
+    ("#", "su", "#", "ZH UW"),
+    ("#", "ssu", "#", "SH UW"),
+    ("#", "sed", " ", "Z D"),
+    ("#", "s", "#", "Z"),
+    ("", "said", "", "S EH D"),
+    ("^", "sion", "", "SH AH N"),
+    ("", "s", "s", ""),
+    (".", "s", " ", "Z"),
+    ("#:.e", "s", " ", "Z"),
+    ("#:^##", "s", " ", "Z"),
+    ("#:^#", "s", " ", "S"),
+    ("u", "s", " ", "S"),
+    (" :#", "s", " ", "Z"),
+    (" ", "sch", "", "S K"),
+    ("", "s", "c+", ""),
+    ("#", "sm", "", "Z M"),
+    ("", "s", "", "S"),
+    (" ", "the", " ", "DH AH"),
+    ("", "to", " ", "T UW"),
+    ("", "that", " ", "DH AE T"),
+    (" ", "this", " ", "DH IH S"),
+    (" ", "they", "", "DH EY"),
+    (" ", "there", "", "DH EH R"),
+    ("", "ther", "", "DH ER"),
+    ("", "their", "", "DH EH R"),
+    (" ", "than", " ", "DH AE N"),
+    (" ", "them", " ", "DH EH M"),
+    ("", "these", " ", "DH IY Z"),
+    (" ", "then", "", "DH EH N"),
+    ("", "through", "", "TH R UW"),
+    ("", "those", "", "DH OW Z"),
+    ("", "though", " ", "DH OW"),
+    (" ", "thus", "", "DH AH S"),
+    ("", "th", "", "TH"),
+    ("#:", "ted", " ", "T IH D"),
+    ("s", "ti", "#n", "CH"),
+    ("", "ti", "o", "SH"),
+    ("", "ti", "a", "SH"),
+    ("", "tien", "", "SH AH N"),
+    ("", "tur", "#", "CH ER"),
+    ("", "tu", "a", "CH UW"),
+    (" ", "two", "", "T UW"),
+    ("", "tch", "", "CH"),
+    ("", "tt", "", "T"),
+    ("", "t", "", "T"),
+    (" ", "un", "i", "Y UW N"),
+    (" ", "un", "", "AH N"),
+    (" ", "upon", "", "AH P AO N"),
+    ("@", "ur", "#", "UH R"),
+    ("", "ur", "#", "Y UH R"),
+    ("", "ur", "", "ER"),
+    ("", "u", "^ ", "AH"),
+    ("", "u", "^^", "AH"),
+    ("", "uy", "", "AY"),
+    (" g", "u", "#", ""),
+    ("g", "u", "%", ""),
+    ("g", "u", "#", "W"),
+    ("#n", "u", "", "Y UW"),
+    ("@", "u", "", "UW"),
+    ("", "u", "", "Y UW"),
+    ("", "view", "", "V Y UW"),
+    ("", "v", "", "V"),
+    (" ", "were", "", "W ER"),
+    ("", "wa", "s", "W AA"),
+    ("", "wa", "t", "W AA"),
+    ("", "where", "", "W EH R"),
+    ("", "what", "", "W AH T"),
+    ("", "whol", "", "HH OW L"),
+    ("", "who", "", "HH UW"),
+    ("", "wh", "", "W"),
+    ("", "war", "", "W AO R"),
+    ("", "wor", "^", "W ER"),
+    ("", "wr", "", "R"),
+    ("", "w", "", "W"),
+    (" ", "x", "", "Z"),
+    ("", "x", "", "K S"),
+    ("", "young", "", "Y AH NG"),
+    (" ", "you", "", "Y UW"),
+    (" ", "yes", "", "Y EH S"),
+    (" ", "y", "", "Y"),
+    ("#:^", "y", " ", "IY"),
+    ("#:^", "y", "i", "IY"),
+    (" :", "y", " ", "AY"),
+    (" :", "y", "#", "AY"),
+    (" :", "y", "^+:#", "IH"),
+    (" :", "y", "^#", "AY"),
+    ("", "y", "", "IH"),
+    ("", "zz", "", "Z"),
+    ("", "z", "", "Z"),
+];
+
+#[derive(Debug, Clone)]
+struct SpellingRule {
+    left: Vec<char>,
+    letters: Vec<char>,
+    right: Vec<char>,
+    phones: Vec<Symbol>,
+}
+
+/// Predicts pronunciations from a fixed set of English spelling rules
+///
+/// The rules don't know anything about stress, so the first full vowel of the prediction is
+/// given primary stress and every other vowel is left unstressed.
+#[derive(Debug, Clone)]
+pub struct LetterRules {
+    rules: HashMap<char, Vec<SpellingRule>>,
+}
+
+impl LetterRules {
+    /// Creates the built-in set of English spelling rules
+    pub fn english() -> LetterRules {
+        let mut rules: HashMap<char, Vec<SpellingRule>> = HashMap::new();
+        for &(left, letters, right, phones) in ENGLISH_RULES {
+            let phones = phones
+                .split_whitespace()
+                .map(|p| Symbol::from_str(p).expect("built-in rules use valid symbols"))
+                .collect();
+            let rule = SpellingRule {
+                left: left.chars().collect(),
+                letters: letters.chars().collect(),
+                right: right.chars().collect(),
+                phones,
+            };
+            rules.entry(rule.letters[0]).or_default().push(rule);
+        }
+        LetterRules { rules }
+    }
+}
+
+impl Default for LetterRules {
+    fn default() -> LetterRules {
+        LetterRules::english()
+    }
+}
+
+impl Predictor for LetterRules {
+    fn predict(&self, word: &str) -> Vec<Symbol> {
+        let word = normalize(word);
+        let mut phones = Vec::new();
+        let mut pos = 0;
+        while pos < word.len() {
+            let matched = self.rules.get(&word[pos]).and_then(|rules| {
+                rules.iter().find(|rule| {
+                    word[pos..].starts_with(&rule.letters)
+                        && left_matches(&rule.left, &word, pos)
+                        && right_matches(&rule.right, &word, pos + rule.letters.len())
+                })
+            });
+            match matched {
+                Some(rule) => {
+                    phones.extend_from_slice(&rule.phones);
+                    pos += rule.letters.len();
+                }
+                None => pos += 1,
+            }
+        }
+        assign_stress(&mut phones);
+        phones
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+fn is_consonant(c: char) -> bool {
+    c.is_ascii_lowercase() && !is_vowel(c)
+}
+
+fn is_voiced(c: char) -> bool {
+    matches!(c, 'b' | 'd' | 'v' | 'g' | 'j' | 'l' | 'm' | 'n' | 'r' | 'w' | 'z')
+}
+
+fn is_front(c: char) -> bool {
+    matches!(c, 'e' | 'i' | 'y')
+}
+
+fn is_sibilant(c: char) -> bool {
+    matches!(c, 's' | 'c' | 'g' | 'z' | 'x' | 'j')
+}
+
+fn is_coronal(c: char) -> bool {
+    matches!(c, 't' | 's' | 'r' | 'd' | 'l' | 'z' | 'n' | 'j')
+}
+
+// matches a left context backwards from the letter before `end`
+fn left_matches(pattern: &[char], word: &[char], end: usize) -> bool {
+    let mut pos = end as isize - 1;
+    let at = |pos: isize| if pos >= 0 { Some(word[pos as usize]) } else { None };
+    for &p in pattern.iter().rev() {
+        match p {
+            ' ' => {
+                if at(pos).is_some_and(|c| c.is_ascii_lowercase()) {
+                    return false;
+                }
+            }
+            '#' => {
+                if !at(pos).is_some_and(is_vowel) {
+                    return false;
+                }
+                while at(pos).is_some_and(is_vowel) {
+                    pos -= 1;
+                }
+            }
+            ':' => {
+                while at(pos).is_some_and(is_consonant) {
+                    pos -= 1;
+                }
+            }
+            _ => {
+                let ok = at(pos).is_some_and(|c| single_matches(p, c));
+                if !ok {
+                    return false;
+                }
+                pos -= 1;
+            }
+        }
+    }
+    true
+}
+
+// matches a right context forwards from `start`
+fn right_matches(pattern: &[char], word: &[char], start: usize) -> bool {
+    let mut pos = start;
+    let at = |pos: usize| word.get(pos).copied();
+    for &p in pattern {
+        match p {
+            ' ' => {
+                if at(pos).is_some_and(|c| c.is_ascii_lowercase()) {
+                    return false;
+                }
+            }
+            '#' => {
+                if !at(pos).is_some_and(is_vowel) {
+                    return false;
+                }
+                while at(pos).is_some_and(is_vowel) {
+                    pos += 1;
+                }
+            }
+            ':' => {
+                while at(pos).is_some_and(is_consonant) {
+                    pos += 1;
+                }
+            }
+            '%' => {
+                let rest: String = word[pos..].iter().collect();
+                match ["ing", "ely", "er", "es", "ed", "e"].iter().find(|s| rest.starts_with(*s)) {
+                    Some(suffix) => pos += suffix.len(),
+                    None => return false,
+                }
+            }
+            _ => {
+                let ok = at(pos).is_some_and(|c| single_matches(p, c));
+                if !ok {
+                    return false;
+                }
+                pos += 1;
+            }
+        }
+    }
+    true
+}
+
+fn single_matches(pattern: char, c: char) -> bool {
+    match pattern {
+        '^' => is_consonant(c),
+        '.' => is_voiced(c),
+        '+' => is_front(c),
+        '&' => is_sibilant(c),
+        '@' => is_coronal(c),
+        p => p == c,
+    }
+}
+
+/* Trained prediction */
+
+// how many letters either side of the current one the model looks at
+const WINDOW: usize = 3;
+
+/// Predicts pronunciations by looking at the letters surrounding each letter of a word
+///
+/// The model is trained by aligning the letters of every word in a dictionary with its
+/// phonemes, then remembering which phonemes each letter produced given up to three letters of
+/// context either side. Predictions use the widest context that was seen during training.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ContextModel {
+    contexts: HashMap<u64, u32>,
+    outputs: Vec<Vec<Symbol>>,
+}
+
+impl ContextModel {
+    /// Trains a model on every pronunciation in a dictionary
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, ContextModel, Predictor};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict: Cmudict = "cat K AE1 T\nhat HH AE1 T\nhit HH IH1 T\n".parse()?;
+    /// let model = ContextModel::train(&dict);
+    /// assert!(!model.predict("cit").is_empty());
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn train(dict: &Cmudict) -> ContextModel {
+        ContextModel::train_from(dict.iter().flat_map(|(label, rules)| {
+            rules.iter().map(move |rule| (label, rule.pronunciation()))
+        }))
+    }
+
+    /// Trains a model on `(word, pronunciation)` pairs
+    pub fn train_from<'a, I>(entries: I) -> ContextModel
+    where
+        I: IntoIterator<Item = (&'a str, &'a [Symbol])>,
+    {
+        let mut entries: Vec<(Vec<char>, &[Symbol])> = entries
+            .into_iter()
+            .map(|(word, phones)| (normalize(word), phones))
+            .collect();
+        // ties between outputs go to the one seen first, so the entries are put in order for
+        // the same dictionary to always train the same model
+        entries.sort_by(|a, b| {
+            let symbols = |phones: &'a [Symbol]| phones.iter().map(|s| s.to_string());
+            a.0.cmp(&b.0).then_with(|| symbols(a.1).cmp(symbols(b.1)))
+        });
+        let aligner = LetterAligner::train(entries.iter().map(|(w, p)| (&w[..], *p)));
+
+        let mut output_ids: HashMap<Vec<Symbol>, u32> = HashMap::new();
+        let mut outputs = Vec::new();
+        let mut counts: HashMap<u64, Vec<(u32, u32)>> = HashMap::new();
+        for (word, phones) in &entries {
+            let chunks = match aligner.align(word, phones) {
+                Some(chunks) => chunks,
+                None => continue,
+            };
+            for (pos, chunk) in chunks.iter().enumerate() {
+                let output = phones[chunk.clone()].to_vec();
+                let id = *output_ids.entry(output.clone()).or_insert_with(|| {
+                    outputs.push(output);
+                    outputs.len() as u32 - 1
+                });
+                for width in 0..=WINDOW {
+                    let seen = counts.entry(context_key(word, pos, width)).or_default();
+                    match seen.iter_mut().find(|(o, _)| *o == id) {
+                        Some((_, count)) => *count += 1,
+                        None => seen.push((id, 1)),
+                    }
+                }
+            }
+        }
+
+        // only keep a context if it predicts something different to the narrower one inside it
+        let mut best: HashMap<u64, u32> = counts
+            .into_iter()
+            .map(|(key, seen)| {
+                let (id, _) = seen
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                    .expect("every context has at least one output");
+                (key, id)
+            })
+            .collect();
+        let mut contexts = HashMap::new();
+        for width in 0..=WINDOW {
+            let keys: Vec<u64> = best.keys().copied().filter(|k| key_width(*k) == width).collect();
+            for key in keys {
+                let id = best[&key];
+                let inherited = if width == 0 { None } else { best.get(&narrow_key(key)).copied() };
+                if inherited != Some(id) {
+                    contexts.insert(key, id);
+                }
+            }
+            // narrower contexts are no longer needed once the next width has been compared
+            if width > 0 {
+                best.retain(|k, _| key_width(*k) != width - 1);
+            }
+        }
+
+        ContextModel { contexts, outputs }
+    }
+}
+
+impl Predictor for ContextModel {
+    fn predict(&self, word: &str) -> Vec<Symbol> {
+        let word = normalize(word);
+        let mut phones = Vec::new();
+        for pos in 0..word.len() {
+            let found = (0..=WINDOW)
+                .rev()
+                .find_map(|width| self.contexts.get(&context_key(&word, pos, width)));
+            if let Some(&id) = found {
+                phones.extend_from_slice(&self.outputs[id as usize]);
+            }
+        }
+        assign_stress(&mut phones);
+        phones
+    }
+}
+
+// lowercases a word and removes the variant marker from dictionary labels
+pub(crate) fn normalize(word: &str) -> Vec<char> {
+    let word = match word.rfind('(') {
+        Some(idx) if word.ends_with(')') => &word[..idx],
+        _ => word,
+    };
+    word.chars().flat_map(char::to_lowercase).collect()
+}
+
+fn letter_code(c: Option<char>) -> u64 {
+    match c {
+        None => 0,
+        Some(c @ 'a'..='z') => c as u64 - 'a' as u64 + 1,
+        Some('\'') => 27,
+        Some('-') => 28,
+        Some('.') => 29,
+        Some(_) => 30,
+    }
+}
+
+// packs the letters within `width` of `pos` into a single key, five bits per letter
+fn context_key(word: &[char], pos: usize, width: usize) -> u64 {
+    let mut key = width as u64;
+    for offset in 0..=2 * width {
+        let idx = (pos + offset).checked_sub(width);
+        key = (key << 5) | letter_code(idx.and_then(|i| word.get(i).copied()));
+    }
+    key
+}
+
+fn key_width(key: u64) -> usize {
+    let mut width = 0;
+    while (key >> (5 * (2 * width + 1))) as usize != width {
+        width += 1;
+    }
+    width
+}
+
+fn narrow_key(key: u64) -> u64 {
+    let width = key_width(key);
+    let letters = (key >> 5) & ((1 << (5 * (2 * width - 1))) - 1);
+    ((width as u64 - 1) << (5 * (2 * width - 1))) | letters
+}
+
+// gives primary stress to the first full vowel when a prediction has none, and makes sure
+// there is only one primary stress
+fn assign_stress(phones: &mut [Symbol]) {
+    let mut primary = false;
+    for phone in phones.iter_mut() {
+        if phone.is_primary() {
+            if primary {
+                *phone = phone.with_stress(Stress::Secondary);
+            }
+            primary = true;
+        }
+    }
+    if primary {
+        return;
+    }
+    let target = phones
+        .iter()
+        .position(|s| s.is_syllable() && *s != Symbol::AH(Stress::None))
+        .or_else(|| phones.iter().position(Symbol::is_syllable));
+    if let Some(idx) = target {
+        phones[idx] = phones[idx].with_stress(Stress::Primary);
+    }
+}
+
+/* Alignment */
+
+type Chunk = (Option<Symbol>, Option<Symbol>);
+
+const UNSEEN: f64 = -12.0;
+
+/// Aligns each letter of a word with zero, one or two phonemes
+///
+/// The letter/phoneme probabilities are estimated with a few rounds of hard EM: every word is
+/// aligned with the current probabilities, and the probabilities are re-estimated from the
+/// alignments.
+pub(crate) struct LetterAligner {
+    scores: HashMap<(char, Chunk), f64>,
+}
+
+impl LetterAligner {
+    pub(crate) fn train<'a, I>(entries: I) -> LetterAligner
+    where
+        I: Iterator<Item = (&'a [char], &'a [Symbol])> + Clone,
+    {
+        let mut aligner = LetterAligner { scores: HashMap::new() };
+        for _ in 0..3 {
+            let mut counts: HashMap<(char, Chunk), f64> = HashMap::new();
+            let mut totals: HashMap<char, f64> = HashMap::new();
+            for (word, phones) in entries.clone() {
+                if let Some(ranges) = aligner.align(word, phones) {
+                    for (&letter, range) in word.iter().zip(ranges) {
+                        let chunk = chunk_of(&phones[range]);
+                        *counts.entry((letter, chunk)).or_insert(0.0) += 1.0;
+                        *totals.entry(letter).or_insert(0.0) += 1.0;
+                    }
+                }
+            }
+            aligner.scores = counts
+                .into_iter()
+                .map(|((letter, chunk), count)| {
+                    ((letter, chunk), (count / totals[&letter]).ln())
+                })
+                .collect();
+        }
+        aligner
+    }
+
+    fn score(&self, letter: char, chunk: Chunk) -> f64 {
+        if self.scores.is_empty() {
+            // before any training, prefer one phoneme per letter
+            return match chunk {
+                (None, _) => -1.0,
+                (Some(_), None) => 0.0,
+                (Some(_), Some(_)) => -2.0,
+            };
+        }
+        match self.scores.get(&(letter, chunk)) {
+            Some(&score) => score,
+            None if chunk.1.is_some() => 2.0 * UNSEEN,
+            None => UNSEEN,
+        }
+    }
+
+    // returns the range of phonemes belonging to each letter
+    pub(crate) fn align(
+        &self,
+        word: &[char],
+        phones: &[Symbol],
+    ) -> Option<Vec<std::ops::Range<usize>>> {
+        let (n, m) = (word.len(), phones.len());
+        if n == 0 || m > 2 * n {
+            return None;
+        }
+        let phones: Vec<Symbol> = phones.iter().map(|s| s.with_stress(Stress::None)).collect();
+        let mut best = vec![vec![f64::NEG_INFINITY; m + 1]; n + 1];
+        let mut back = vec![vec![0usize; m + 1]; n + 1];
+        best[0][0] = 0.0;
+        for i in 0..n {
+            for j in 0..=m {
+                if best[i][j] == f64::NEG_INFINITY {
+                    continue;
+                }
+                for len in 0..=2 {
+                    if j + len > m {
+                        break;
+                    }
+                    let score = best[i][j] + self.score(word[i], chunk_of(&phones[j..j + len]));
+                    if score > best[i + 1][j + len] {
+                        best[i + 1][j + len] = score;
+                        back[i + 1][j + len] = len;
+                    }
+                }
+            }
+        }
+        if best[n][m] == f64::NEG_INFINITY {
+            return None;
+        }
+        let mut ranges = vec![0..0; n];
+        let mut j = m;
+        for i in (1..=n).rev() {
+            let len = back[i][j];
+            ranges[i - 1] = j - len..j;
+            j -= len;
+        }
+        Some(ranges)
+    }
+}
+
+fn chunk_of(phones: &[Symbol]) -> Chunk {
+    (phones.first().copied(), phones.get(1).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn letter_rules() {
+        let rules = LetterRules::english();
+        assert_eq!(
+            rules.predict("cat"),
+            vec![Symbol::K, Symbol::AE(Stress::Primary), Symbol::T]
+        );
+        assert_eq!(
+            rules.predict("Shape"),
+            vec![Symbol::SH, Symbol::EY(Stress::Primary), Symbol::P]
+        );
+        assert_eq!(
+            rules.predict("catch"),
+            vec![Symbol::K, Symbol::AE(Stress::Primary), Symbol::CH]
+        );
+        assert!(rules.predict("123").is_empty());
+    }
+
+    #[test]
+    fn context_keys() {
+        let word: Vec<char> = "catch".chars().collect();
+        for width in 1..=WINDOW {
+            let key = context_key(&word, 2, width);
+            assert_eq!(key_width(key), width);
+            assert_eq!(narrow_key(key), context_key(&word, 2, width - 1));
+        }
+    }
+
+    #[test]
+    fn trained_model() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let model = ContextModel::train_from(crate::sample(&d, &["a", "b"]));
+        assert_eq!(model.predict("apple"), d.get("apple").unwrap()[0].pronunciation());
+        assert_eq!(
+            model.predict("blatch"),
+            vec![Symbol::B, Symbol::L, Symbol::AE(Stress::Primary), Symbol::CH]
+        );
+    }
+
+    #[test]
+    fn training_order() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let entries: Vec<(&str, &[Symbol])> = crate::sample(&d, &["ca", "ma"]).collect();
+        let forward = ContextModel::train_from(entries.iter().copied());
+        let backward = ContextModel::train_from(entries.iter().rev().copied());
+        assert_eq!(forward.outputs, backward.outputs);
+        assert_eq!(forward.contexts, backward.contexts);
+    }
+}
@@ -0,0 +1,344 @@
+//! Conversion between ARPAbet symbols and the International Phonetic Alphabet
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{ParseError, ParseResult};
+use crate::syllable::syllabify;
+
+/// Options controlling how symbols are written in IPA
+///
+/// The default follows the usual broad transcription of General American English.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpaStyle {
+    /// Write unstressed `AH` as ə and `ER` as ɚ, keeping ʌ and ɝ for stressed syllables
+    pub reduce_unstressed: bool,
+    /// Put primary (ˈ) and secondary (ˌ) stress marks before stressed syllables
+    pub stress_marks: bool,
+    /// Mark stress even in words of a single syllable
+    pub mark_monosyllables: bool,
+    /// Separate syllables that don't start with a stress mark with a period
+    pub syllable_breaks: bool,
+    /// Write the tense vowels of `AA`, `AO`, `ER`, `IY` and `UW` with a length mark (ː)
+    pub length_marks: bool,
+    /// Join the two halves of the affricates `CH` and `JH` with a tie bar
+    pub tie_bars: bool,
+}
+
+impl Default for IpaStyle {
+    fn default() -> IpaStyle {
+        IpaStyle {
+            reduce_unstressed: true,
+            stress_marks: true,
+            mark_monosyllables: false,
+            syllable_breaks: false,
+            length_marks: false,
+            tie_bars: false,
+        }
+    }
+}
+
+impl Symbol {
+    /// Writes the symbol in IPA
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{IpaStyle, Symbol, Stress};
+    ///
+    /// let style = IpaStyle::default();
+    /// assert_eq!(Symbol::AH(Stress::Primary).to_ipa(&style), "ʌ");
+    /// assert_eq!(Symbol::AH(Stress::None).to_ipa(&style), "ə");
+    /// assert_eq!(Symbol::CH.to_ipa(&style), "tʃ");
+    /// ```
+    pub fn to_ipa(&self, style: &IpaStyle) -> String {
+        use crate::core::Symbol::*;
+        let reduced = style.reduce_unstressed && self.is_unstressed();
+        let long = if style.length_marks { "ː" } else { "" };
+        let ipa = match *self {
+            AA(_) => return format!("ɑ{}", long),
+            AE(_) => "æ",
+            AH(_) if reduced => "ə",
+            AH(_) => "ʌ",
+            AO(_) => return format!("ɔ{}", long),
+            AW(_) => "aʊ",
+            AY(_) => "aɪ",
+            B => "b",
+            CH if style.tie_bars => "t͡ʃ",
+            CH => "tʃ",
+            D => "d",
+            DH => "ð",
+            EH(_) => "ɛ",
+            ER(_) if reduced => "ɚ",
+            ER(_) => return format!("ɝ{}", long),
+            EY(_) => "eɪ",
+            F => "f",
+            G => "ɡ",
+            HH => "h",
+            IH(_) => "ɪ",
+            IY(_) => return format!("i{}", long),
+            JH if style.tie_bars => "d͡ʒ",
+            JH => "dʒ",
+            K => "k",
+            L => "l",
+            M => "m",
+            N => "n",
+            NG => "ŋ",
+            OW(_) => "oʊ",
+            OY(_) => "ɔɪ",
+            P => "p",
+            R => "ɹ",
+            S => "s",
+            SH => "ʃ",
+            T => "t",
+            TH => "θ",
+            UH(_) => "ʊ",
+            UW(_) => return format!("u{}", long),
+            V => "v",
+            W => "w",
+            Y => "j",
+            Z => "z",
+            ZH => "ʒ",
+        };
+        ipa.to_string()
+    }
+}
+
+impl Rule {
+    /// Writes the pronunciation in IPA, with stress marks placed before stressed syllables
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, IpaStyle};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let apple = &dict.get("apple").unwrap()[0];
+    /// assert_eq!(apple.to_ipa(&IpaStyle::default()), "ˈæpəl");
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn to_ipa(&self, style: &IpaStyle) -> String {
+        to_ipa(self.pronunciation(), style)
+    }
+}
+
+/// Writes a pronunciation in IPA, with stress marks placed before stressed syllables
+pub fn to_ipa(phones: &[Symbol], style: &IpaStyle) -> String {
+    let syllables = syllabify(phones);
+    if syllables.is_empty() {
+        return phones.iter().map(|s| s.to_ipa(style)).collect();
+    }
+    let mark_stress = style.stress_marks && (style.mark_monosyllables || syllables.len() > 1);
+    let mut ipa = String::new();
+    for (idx, syllable) in syllables.iter().enumerate() {
+        let mark = match syllable.stress() {
+            Stress::Primary if mark_stress => Some('ˈ'),
+            Stress::Secondary if mark_stress => Some('ˌ'),
+            _ => None,
+        };
+        match mark {
+            Some(mark) => ipa.push(mark),
+            None if style.syllable_breaks && idx > 0 => ipa.push('.'),
+            None => {}
+        }
+        for symbol in syllable.symbols() {
+            ipa.push_str(&symbol.to_ipa(style));
+        }
+    }
+    ipa
+}
+
+/// Reads a pronunciation written in IPA
+///
+/// Stress marks give their stress to the next vowel. ʌ and ɝ are only written for stressed vowels,
+/// so they have primary stress even without a mark, and so does the vowel of a word of one
+/// syllable, since those are usually written without marks, unless it is ə or ɚ. Every other vowel
+/// is unstressed. Syllable breaks, length marks, tie bars, spaces and enclosing slashes or brackets
+/// are ignored. Common alternative symbols for the same sounds, such as `r` for `ɹ`, `g` for `ɡ`
+/// and `ɜ` for `ɝ`, are also understood.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{parse_ipa, Symbol, Stress};
+///
+/// assert_eq!(
+///     parse_ipa("/ˈæpəl/"),
+///     Ok(vec![
+///         Symbol::AE(Stress::Primary),
+///         Symbol::P,
+///         Symbol::AH(Stress::None),
+///         Symbol::L,
+///     ])
+/// );
+/// ```
+pub fn parse_ipa(s: &str) -> ParseResult<Vec<Symbol>> {
+    use crate::core::Symbol::*;
+    let chars: Vec<char> = s
+        .chars()
+        .filter(|c| !matches!(c, 'ː' | 'ˑ' | '\u{361}' | '\u{35c}' | '.' | '/' | '[' | ']'))
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let mut symbols = Vec::new();
+    let mut stress = Stress::None;
+    let mut reduced = false;
+    let mut pos = 0;
+    while pos < chars.len() {
+        let next = chars.get(pos + 1).copied();
+        // (symbol, characters used)
+        let (symbol, len) = match (chars[pos], next) {
+            ('ˈ', _) | ('\'', _) => {
+                stress = Stress::Primary;
+                pos += 1;
+                continue;
+            }
+            ('ˌ', _) | (',', _) => {
+                stress = Stress::Secondary;
+                pos += 1;
+                continue;
+            }
+            ('a', Some('ʊ')) => (AW(stress), 2),
+            ('a', Some('ɪ')) => (AY(stress), 2),
+            ('e', Some('ɪ')) => (EY(stress), 2),
+            ('o', Some('ʊ')) => (OW(stress), 2),
+            ('ɔ', Some('ɪ')) => (OY(stress), 2),
+            ('t', Some('ʃ')) => (CH, 2),
+            ('d', Some('ʒ')) => (JH, 2),
+            ('ɑ', _) | ('ɒ', _) => (AA(stress), 1),
+            ('æ', _) => (AE(stress), 1),
+            ('ʌ', _) => (AH(stressed(stress)), 1),
+            ('ə', _) => {
+                reduced = true;
+                (AH(stress), 1)
+            }
+            ('ɔ', _) => (AO(stress), 1),
+            ('ɛ', _) => (EH(stress), 1),
+            ('ɝ', _) | ('ɜ', _) => (ER(stressed(stress)), 1),
+            ('ɚ', _) => {
+                reduced = true;
+                (ER(stress), 1)
+            }
+            ('e', _) => (EY(stress), 1),
+            ('o', _) => (OW(stress), 1),
+            ('ɪ', _) => (IH(stress), 1),
+            ('i', _) => (IY(stress), 1),
+            ('ʊ', _) => (UH(stress), 1),
+            ('u', _) => (UW(stress), 1),
+            ('b', _) => (B, 1),
+            ('d', _) => (D, 1),
+            ('ð', _) => (DH, 1),
+            ('f', _) => (F, 1),
+            ('ɡ', _) | ('g', _) => (G, 1),
+            ('h', _) => (HH, 1),
+            ('k', _) => (K, 1),
+            ('l', _) | ('ɫ', _) => (L, 1),
+            ('m', _) => (M, 1),
+            ('n', _) => (N, 1),
+            ('ŋ', _) => (NG, 1),
+            ('p', _) => (P, 1),
+            ('ɹ', _) | ('r', _) => (R, 1),
+            ('s', _) => (S, 1),
+            ('ʃ', _) => (SH, 1),
+            ('t', _) => (T, 1),
+            ('θ', _) => (TH, 1),
+            ('v', _) => (V, 1),
+            ('w', _) => (W, 1),
+            ('j', _) => (Y, 1),
+            ('z', _) => (Z, 1),
+            ('ʒ', _) => (ZH, 1),
+            (c, _) => return Err(ParseError::UnexpectedCharacter("an IPA symbol", c)),
+        };
+        if symbol.is_syllable() {
+            stress = Stress::None;
+        }
+        symbols.push(symbol);
+        pos += len;
+    }
+    let mut vowels = symbols.iter_mut().filter(|s| s.is_syllable());
+    if let (Some(vowel), None) = (vowels.next(), vowels.next()) {
+        if vowel.is_unstressed() && !reduced {
+            *vowel = vowel.with_stress(Stress::Primary);
+        }
+    }
+    Ok(symbols)
+}
+
+fn stressed(stress: Stress) -> Stress {
+    match stress {
+        Stress::None => Stress::Primary,
+        stress => stress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+    use crate::Cmudict;
+
+    #[test]
+    fn writing() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let style = IpaStyle::default();
+        let ipa = |word: &str| d.get(word).unwrap()[0].to_ipa(&style);
+        assert_eq!(ipa("butter"), "ˈbʌtɚ");
+        assert_eq!(ipa("nurse"), "nɝs");
+        assert_eq!(ipa("abstract"), "æbˈstɹækt");
+        assert_eq!(d.get("abstract").unwrap()[1].to_ipa(&style), "ˈæbˌstɹækt");
+        assert_eq!(ipa("church"), "tʃɝtʃ");
+
+        let narrow = IpaStyle {
+            reduce_unstressed: false,
+            mark_monosyllables: true,
+            syllable_breaks: true,
+            length_marks: true,
+            tie_bars: true,
+            ..IpaStyle::default()
+        };
+        assert_eq!(d.get("church").unwrap()[0].to_ipa(&narrow), "ˈt͡ʃɝːt͡ʃ");
+        assert_eq!(d.get("banana").unwrap()[0].to_ipa(&narrow), "bʌˈnæ.nʌ");
+    }
+
+    #[test]
+    fn reading() {
+        assert_eq!(
+            parse_ipa("[ˈæbˌstɹækt]"),
+            Ok(vec![AE(Stress::Primary), B, S, T, R, AE(Stress::Secondary), K, T])
+        );
+        assert_eq!(parse_ipa("t͡ʃɜːtʃ"), Ok(vec![CH, ER(Stress::Primary), CH]));
+        assert_eq!(parse_ipa("kæt"), Ok(vec![K, AE(Stress::Primary), T]));
+        assert_eq!(parse_ipa("ðə"), Ok(vec![DH, AH(Stress::None)]));
+        assert_eq!(parse_ipa("bʌtəɹ"), Ok(vec![B, AH(Stress::Primary), T, AH(Stress::None), R]));
+        assert_eq!(
+            parse_ipa("ˈbʌ.tɚ"),
+            Ok(vec![B, AH(Stress::Primary), T, ER(Stress::None)])
+        );
+        assert_eq!(
+            parse_ipa("kæx"),
+            Err(ParseError::UnexpectedCharacter("an IPA symbol", 'x'))
+        );
+
+        let style = IpaStyle::default();
+        for ipa in &["ˈæbˌstɹækt", "ˈbʌtɚ", "ˌaʊtˈsaɪd", "ɹʌst", "ðə"] {
+            assert_eq!(&to_ipa(&parse_ipa(ipa).unwrap(), &style), ipa);
+        }
+
+        // words of one syllable are written without stress marks, and keep their stress
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        for word in &["rust", "nurse", "cat", "a"] {
+            let rule = &d.get(word).unwrap()[0];
+            assert_eq!(parse_ipa(&rule.to_ipa(&style)).unwrap(), rule.pronunciation());
+        }
+    }
+}
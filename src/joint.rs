@@ -0,0 +1,561 @@
+//! A trainable joint-sequence model for letter-to-sound prediction
+//!
+//! Every word in the training data is aligned so that each letter produces zero, one or two
+//! phonemes. Each letter together with its phonemes makes up a "graphone", and an n-gram model
+//! with Witten-Bell smoothing is estimated over the sequences of graphones. Predicting a
+//! pronunciation is then a matter of finding the most likely sequence of graphones that spells
+//! the word.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::Symbol;
+use crate::errors::{Error, Result};
+use crate::g2p::{normalize, LetterAligner, Predictor};
+use crate::Cmudict;
+
+// tokens 0 and 1 mark the start and end of a word, graphones start after them
+const START: u32 = 0;
+const END: u32 = 1;
+const FIRST_GRAPHONE: u32 = 2;
+
+/// The longest n-grams a `JointModel` can use
+pub const MAX_ORDER: usize = 4;
+
+// how many partial pronunciations are kept while decoding
+const BEAM: usize = 24;
+
+/// A joint-sequence (graphone n-gram) model of how words are pronounced
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, JointModel, Predictor};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict: Cmudict = "cat K AE1 T\nhat HH AE1 T\nhit HH IH1 T\n".parse()?;
+/// let model = JointModel::train(&dict, 3);
+///
+/// let saved = model.to_string();
+/// let loaded: JointModel = saved.parse()?;
+/// assert_eq!(model.predict("cit"), loaded.predict("cit"));
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JointModel {
+    order: usize,
+    graphones: Vec<(char, Vec<Symbol>)>,
+    // the count of each n-gram of the highest order, which is all that gets saved
+    grams: HashMap<u64, u32>,
+    // derived from `grams`
+    counts: HashMap<u64, u32>,
+    contexts: HashMap<u64, (u32, u32)>,
+    by_letter: HashMap<char, Vec<u32>>,
+}
+
+impl JointModel {
+    /// Trains a model of the given order on every pronunciation in a dictionary
+    ///
+    /// The order is the length of the graphone n-grams, and is clamped between 1 and
+    /// `MAX_ORDER`.
+    pub fn train(dict: &Cmudict, order: usize) -> JointModel {
+        JointModel::train_from(entries(dict), order)
+    }
+
+    /// Trains a model of the given order on `(word, pronunciation)` pairs
+    pub fn train_from<'a, I>(entries: I, order: usize) -> JointModel
+    where
+        I: IntoIterator<Item = (&'a str, &'a [Symbol])>,
+    {
+        let order = order.clamp(1, MAX_ORDER);
+        let mut entries: Vec<(Vec<char>, &[Symbol])> = entries
+            .into_iter()
+            .map(|(word, phones)| (normalize(word), phones))
+            .collect();
+        // graphones are numbered as they are first seen, and the numbers break ties when
+        // decoding and order the saved model, so they are seen in the same order every time
+        entries.sort_by(|a, b| {
+            let symbols = |phones: &'a [Symbol]| phones.iter().map(|s| s.to_string());
+            a.0.cmp(&b.0).then_with(|| symbols(a.1).cmp(symbols(b.1)))
+        });
+        let aligner = LetterAligner::train(entries.iter().map(|(w, p)| (&w[..], *p)));
+
+        let mut ids: HashMap<(char, Vec<Symbol>), u32> = HashMap::new();
+        let mut graphones = Vec::new();
+        let mut grams = HashMap::new();
+        for (word, phones) in &entries {
+            let ranges = match aligner.align(word, phones) {
+                Some(ranges) => ranges,
+                None => continue,
+            };
+            let mut tokens = vec![START; order - 1];
+            for (&letter, range) in word.iter().zip(ranges) {
+                let graphone = (letter, phones[range].to_vec());
+                let id = *ids.entry(graphone.clone()).or_insert_with(|| {
+                    graphones.push(graphone);
+                    FIRST_GRAPHONE + graphones.len() as u32 - 1
+                });
+                tokens.push(id);
+            }
+            tokens.push(END);
+            for gram in tokens.windows(order) {
+                *grams.entry(pack(gram)).or_insert(0) += 1;
+            }
+        }
+        assert!(
+            graphones.len() < (1 << 16) - FIRST_GRAPHONE as usize,
+            "too many graphones to pack into n-gram keys"
+        );
+
+        JointModel::from_grams(order, graphones, grams)
+    }
+
+    /// Trains a model on part of a dictionary and evaluates it on the rest
+    ///
+    /// `held_out` is the fraction of words that are kept back from training for evaluation.
+    /// Which words are held out depends only on their spelling, so the split is the same every
+    /// time.
+    pub fn train_and_evaluate(
+        dict: &Cmudict,
+        order: usize,
+        held_out: f64,
+    ) -> (JointModel, Evaluation) {
+        let (train, test) = split(dict, held_out);
+        let model = JointModel::train_from(train, order);
+        let evaluation = evaluate(&model, test);
+        (model, evaluation)
+    }
+
+    /// Loads a model that was saved with `JointModel::save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<JointModel> {
+        let file = File::open(path)?;
+        JointModel::read(BufReader::new(file))
+    }
+
+    /// Saves the model to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Writes the model in its text format
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, ";; cmudict-fast joint sequence model")?;
+        writeln!(writer, "order {}", self.order)?;
+        for (letter, phones) in &self.graphones {
+            write!(writer, "graphone {}", letter)?;
+            for phone in phones {
+                write!(writer, " {}", phone)?;
+            }
+            writeln!(writer)?;
+        }
+        let mut grams: Vec<(Vec<u32>, u32)> = self
+            .grams
+            .iter()
+            .map(|(&key, &count)| (unpack(key, self.order), count))
+            .collect();
+        grams.sort();
+        for (gram, count) in grams {
+            write!(writer, "gram {}", count)?;
+            for token in gram {
+                write!(writer, " {}", token)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the length of the graphone n-grams the model uses
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    fn read<R: BufRead>(reader: R) -> Result<JointModel> {
+        let mut order = None;
+        let mut graphones = Vec::new();
+        let mut grams = HashMap::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.starts_with(";;") || line.trim().is_empty() {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("order") => {
+                    let n = parts
+                        .next()
+                        .and_then(|n| n.parse::<usize>().ok())
+                        .filter(|n| (1..=MAX_ORDER).contains(n))
+                        .ok_or(Error::InvalidModel(idx))?;
+                    order = Some(n);
+                }
+                Some("graphone") => {
+                    let mut letter = parts.next().ok_or(Error::InvalidModel(idx))?.chars();
+                    let letter = match (letter.next(), letter.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err(Error::InvalidModel(idx)),
+                    };
+                    let phones = parts
+                        .map(Symbol::from_str)
+                        .collect::<std::result::Result<_, _>>()?;
+                    graphones.push((letter, phones));
+                }
+                Some("gram") => {
+                    let order = order.ok_or(Error::InvalidModel(idx))?;
+                    let numbers = parts
+                        .map(|n| n.parse::<u32>())
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                        .map_err(|_| Error::InvalidModel(idx))?;
+                    let limit = FIRST_GRAPHONE + graphones.len() as u32;
+                    match numbers.split_first() {
+                        Some((&count, gram))
+                            if gram.len() == order && gram.iter().all(|&t| t < limit) =>
+                        {
+                            grams.insert(pack(gram), count);
+                        }
+                        _ => return Err(Error::InvalidModel(idx)),
+                    }
+                }
+                _ => return Err(Error::InvalidModel(idx)),
+            }
+        }
+        let order = order.ok_or(Error::InvalidModel(0))?;
+        Ok(JointModel::from_grams(order, graphones, grams))
+    }
+
+    fn from_grams(
+        order: usize,
+        graphones: Vec<(char, Vec<Symbol>)>,
+        grams: HashMap<u64, u32>,
+    ) -> JointModel {
+        // every lower order n-gram is the suffix of one of the highest order, because words are
+        // padded with enough start tokens
+        let mut counts: HashMap<u64, u32> = HashMap::new();
+        for (&key, &count) in &grams {
+            let gram = unpack(key, order);
+            for start in 0..order {
+                *counts.entry(pack(&gram[start..])).or_insert(0) += count;
+            }
+        }
+        let mut contexts: HashMap<u64, (u32, u32)> = HashMap::new();
+        for (&key, &count) in &counts {
+            let context = contexts.entry(key >> 16).or_insert((0, 0));
+            context.0 += count;
+            context.1 += 1;
+        }
+        let mut by_letter: HashMap<char, Vec<u32>> = HashMap::new();
+        for (idx, (letter, _)) in graphones.iter().enumerate() {
+            by_letter.entry(*letter).or_default().push(FIRST_GRAPHONE + idx as u32);
+        }
+        JointModel {
+            order,
+            graphones,
+            grams,
+            counts,
+            contexts,
+            by_letter,
+        }
+    }
+
+    // the Witten-Bell smoothed probability of `token` following `history`
+    fn probability(&self, history: &[u32], token: u32) -> f64 {
+        let lower = match history.split_first() {
+            Some((_, shorter)) => self.probability(shorter, token),
+            None => 1.0 / (self.graphones.len() + 1) as f64,
+        };
+        match self.contexts.get(&pack(history)) {
+            Some(&(total, distinct)) => {
+                let mut gram = history.to_vec();
+                gram.push(token);
+                let count = self.counts.get(&pack(&gram)).copied().unwrap_or(0);
+                (count as f64 + distinct as f64 * lower) / (total as f64 + distinct as f64)
+            }
+            None => lower,
+        }
+    }
+}
+
+impl Predictor for JointModel {
+    fn predict(&self, word: &str) -> Vec<Symbol> {
+        let word = normalize(word);
+        // (history, log probability, graphones so far)
+        let mut beam = vec![(vec![START; self.order - 1], 0.0, Vec::new())];
+        for letter in word {
+            let candidates = match self.by_letter.get(&letter) {
+                Some(candidates) => candidates,
+                None => continue,
+            };
+            let mut next: HashMap<Vec<u32>, (f64, Vec<u32>)> = HashMap::new();
+            for (history, score, path) in &beam {
+                for &token in candidates {
+                    let score = score + self.probability(history, token).ln();
+                    let mut history = history.clone();
+                    if !history.is_empty() {
+                        history.remove(0);
+                        history.push(token);
+                    }
+                    let better = match next.get(&history) {
+                        Some((best, _)) => score > *best,
+                        None => true,
+                    };
+                    if better {
+                        let mut path = path.clone();
+                        path.push(token);
+                        next.insert(history, (score, path));
+                    }
+                }
+            }
+            let mut next: Vec<_> = next.into_iter().map(|(h, (s, p))| (h, s, p)).collect();
+            next.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.2.cmp(&b.2)));
+            next.truncate(BEAM);
+            beam = next;
+        }
+        let best = beam
+            .into_iter()
+            .map(|(history, score, path)| (score + self.probability(&history, END).ln(), path))
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then_with(|| b.1.cmp(&a.1)));
+        match best {
+            Some((_, path)) => path
+                .into_iter()
+                .flat_map(|t| self.graphones[(t - FIRST_GRAPHONE) as usize].1.iter().copied())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl FromStr for JointModel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<JointModel> {
+        JointModel::read(Cursor::new(s))
+    }
+}
+
+impl std::fmt::Display for JointModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut buf = Vec::new();
+        self.write(&mut buf).map_err(|_| std::fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buf))
+    }
+}
+
+/// How well a predictor did on a set of words it wasn't trained on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Evaluation {
+    /// The number of words that were predicted
+    pub words: usize,
+    /// The number of words whose prediction didn't match any of their pronunciations
+    pub word_errors: usize,
+    /// The number of phonemes in the closest pronunciation of each word
+    pub phonemes: usize,
+    /// The number of phonemes that had to be inserted, deleted or substituted to turn each
+    /// prediction into the closest pronunciation
+    pub phoneme_errors: usize,
+}
+
+impl Evaluation {
+    /// The fraction of words that were predicted wrongly
+    pub fn word_error_rate(&self) -> f64 {
+        ratio(self.word_errors, self.words)
+    }
+
+    /// The number of phoneme errors per phoneme of the correct pronunciations
+    pub fn phoneme_error_rate(&self) -> f64 {
+        ratio(self.phoneme_errors, self.phonemes)
+    }
+}
+
+fn ratio(errors: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        errors as f64 / total as f64
+    }
+}
+
+/// Measures how well `predictor` pronounces the words in `entries`
+///
+/// Words with several pronunciations are given as several entries, and a prediction is counted
+/// as correct if it matches any of them.
+pub fn evaluate<'a, P, I>(predictor: &P, entries: I) -> Evaluation
+where
+    P: Predictor + ?Sized,
+    I: IntoIterator<Item = (&'a str, &'a [Symbol])>,
+{
+    let mut by_word: Vec<(&str, Vec<&[Symbol]>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (word, phones) in entries {
+        let idx = *index.entry(word).or_insert_with(|| {
+            by_word.push((word, Vec::new()));
+            by_word.len() - 1
+        });
+        by_word[idx].1.push(phones);
+    }
+
+    let mut evaluation = Evaluation::default();
+    for (word, references) in by_word {
+        let predicted = predictor.predict(word);
+        let (errors, length) = references
+            .iter()
+            .map(|r| (edit_distance(&predicted, r), r.len()))
+            .min()
+            .expect("every word has a pronunciation");
+        evaluation.words += 1;
+        evaluation.phonemes += length;
+        evaluation.phoneme_errors += errors;
+        if errors > 0 {
+            evaluation.word_errors += 1;
+        }
+    }
+    evaluation
+}
+
+/// Splits the pronunciations in a dictionary into a training set and a held out test set
+///
+/// Roughly `held_out` of the words end up in the test set. All the pronunciations of a word
+/// end up on the same side, and which side only depends on how the word is spelled.
+pub fn split(dict: &Cmudict, held_out: f64) -> (Vec<Entry<'_>>, Vec<Entry<'_>>) {
+    let threshold = (held_out.clamp(0.0, 1.0) * u32::MAX as f64) as u64;
+    let mut train = Vec::new();
+    let mut test = Vec::new();
+    for (word, phones) in entries(dict) {
+        if (fnv1a(word) as u32 as u64) < threshold {
+            test.push((word, phones));
+        } else {
+            train.push((word, phones));
+        }
+    }
+    (train, test)
+}
+
+// a word along with one of its pronunciations
+type Entry<'a> = (&'a str, &'a [Symbol]);
+
+fn entries(dict: &Cmudict) -> impl Iterator<Item = Entry<'_>> {
+    dict.iter()
+        .flat_map(|(label, rules)| rules.iter().map(move |rule| (label, rule.pronunciation())))
+}
+
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn edit_distance(a: &[Symbol], b: &[Symbol]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitute = diagonal + if x == y { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn pack(tokens: &[u32]) -> u64 {
+    // tokens are offset by one so that n-grams of different lengths never share a key
+    tokens.iter().fold(0, |key, &t| (key << 16) | (t as u64 + 1))
+}
+
+fn unpack(mut key: u64, len: usize) -> Vec<u32> {
+    let mut tokens = vec![0; len];
+    for token in tokens.iter_mut().rev() {
+        *token = (key & 0xffff) as u32 - 1;
+        key >>= 16;
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Stress;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        let dict: Cmudict = "cat K AE1 T\nhat HH AE1 T\nhit HH IH1 T\nsit S IH1 T\n"
+            .parse()
+            .unwrap();
+        let model = JointModel::train(&dict, 3);
+        assert_eq!(
+            model.predict("hat"),
+            vec![Symbol::HH, Symbol::AE(Stress::Primary), Symbol::T]
+        );
+        assert_eq!(
+            model.predict("sat"),
+            vec![Symbol::S, Symbol::AE(Stress::Primary), Symbol::T]
+        );
+
+        let loaded: JointModel = model.to_string().parse().unwrap();
+        assert_eq!(loaded.order(), 3);
+        assert_eq!(loaded.counts, model.counts);
+        assert_eq!(loaded.predict("cit"), model.predict("cit"));
+
+        assert!("order 9\n".parse::<JointModel>().is_err());
+        assert!("order 2\ngram 1 0 40\n".parse::<JointModel>().is_err());
+    }
+
+    #[test]
+    fn training_order() {
+        let dict: Cmudict = "cat K AE1 T\nhat HH AE1 T\nchat CH AE1 T\nthat DH AE1 T\n"
+            .parse()
+            .unwrap();
+        let entries: Vec<(&str, &[Symbol])> = entries(&dict).collect();
+        let forward = JointModel::train_from(entries.iter().copied(), 2);
+        let backward = JointModel::train_from(entries.iter().rev().copied(), 2);
+        assert_eq!(forward.to_string(), backward.to_string());
+    }
+
+    #[test]
+    fn held_out_evaluation() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let (train, test) = split(&d, 0.1);
+        assert!(test.len() * 5 < train.len());
+        let trained: std::collections::HashSet<&str> = train.iter().map(|(w, _)| *w).collect();
+        assert!(test.iter().all(|(word, _)| !trained.contains(word)));
+
+        // keep the test quick by only using a slice of the dictionary
+        let subset = |(word, _): &(&str, &[Symbol])| word.starts_with('c');
+        let train: Vec<_> = train.into_iter().filter(subset).collect();
+        let test: Vec<_> = test.into_iter().filter(subset).take(200).collect();
+        let model = JointModel::train_from(train, 3);
+        let evaluation = evaluate(&model, test);
+        assert!(evaluation.words > 150 && evaluation.words <= 200);
+        assert!(evaluation.word_error_rate() < 0.8);
+        assert!(evaluation.phoneme_error_rate() < 0.3);
+    }
+
+    #[test]
+    fn distances() {
+        use Symbol::*;
+        assert_eq!(edit_distance(&[K, AE(Stress::Primary), T], &[K, AE(Stress::Primary), T]), 0);
+        assert_eq!(edit_distance(&[K, AE(Stress::Primary), T], &[HH, AE(Stress::Primary)]), 2);
+        assert_eq!(edit_distance(&[], &[HH, AE(Stress::Primary)]), 2);
+    }
+}
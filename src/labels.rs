@@ -0,0 +1,247 @@
+//! Searching the words of the dictionary by their spelling
+//!
+//! The words are kept in alphabetical order, and again in the order of their reversed
+//! spellings, so prefix and suffix queries only have to find where their matches start.
+
+use crate::core::Rule;
+use crate::Cmudict;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Labels {
+    pub(crate) sorted: Vec<String>,
+    // each word spelled backwards, with its index in `sorted`
+    reversed: Vec<(String, usize)>,
+}
+
+impl Labels {
+    fn new(dict: &Cmudict) -> Labels {
+        let mut sorted: Vec<String> = dict.map.keys().cloned().collect();
+        sorted.sort_unstable();
+        let mut reversed: Vec<(String, usize)> = sorted
+            .iter()
+            .enumerate()
+            .map(|(idx, word)| (word.chars().rev().collect(), idx))
+            .collect();
+        reversed.sort_unstable();
+        Labels { sorted, reversed }
+    }
+}
+
+impl Cmudict {
+    /// Finds every word that starts with `prefix`, in alphabetical order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::Cmudict;
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let words: Vec<&str> = dict.with_prefix("rusti").iter().map(|(word, _)| *word).collect();
+    /// assert_eq!(words, vec!["rustic", "rustier", "rustiest", "rustigan", "rustin", "rusting"]);
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn with_prefix(&self, prefix: &str) -> Vec<(&str, &[Rule])> {
+        let sorted = &self.labels().sorted;
+        let start = sorted.partition_point(|word| word.as_str() < prefix);
+        let words = sorted[start..].iter().take_while(|word| word.starts_with(prefix));
+        self.entries(words)
+    }
+
+    /// Finds every word that ends with `suffix`, in alphabetical order
+    pub fn with_suffix(&self, suffix: &str) -> Vec<(&str, &[Rule])> {
+        let labels = self.labels();
+        let reversed: String = suffix.chars().rev().collect();
+        let start = labels.reversed.partition_point(|(word, _)| word.as_str() < reversed.as_str());
+        let mut found: Vec<usize> = labels.reversed[start..]
+            .iter()
+            .take_while(|(word, _)| word.starts_with(&reversed))
+            .map(|&(_, idx)| idx)
+            .collect();
+        found.sort_unstable();
+        self.entries(found.into_iter().map(|idx| &labels.sorted[idx]))
+    }
+
+    /// Finds every word with `substring` anywhere in its spelling, in alphabetical order
+    pub fn containing(&self, substring: &str) -> Vec<(&str, &[Rule])> {
+        let words = self.labels().sorted.iter().filter(|word| word.contains(substring));
+        self.entries(words)
+    }
+
+    /// Finds every word whose spelling matches a glob, in alphabetical order
+    ///
+    /// `*` matches any run of characters, `?` matches any one character, and `[abc]` matches
+    /// any character in the brackets, or any character that isn't when written `[!abc]`.
+    /// Everything else matches itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::Cmudict;
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let words: Vec<&str> = dict.glob("c?t").iter().map(|(word, _)| *word).collect();
+    /// assert!(words.contains(&"cat"));
+    /// assert!(words.contains(&"cut"));
+    /// assert!(dict.glob("*tion").iter().all(|(word, _)| word.ends_with("tion")));
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn glob(&self, pattern: &str) -> Vec<(&str, &[Rule])> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        // only the words starting with the literal part of the glob can match
+        let prefix: String = pattern
+            .iter()
+            .take_while(|c| !matches!(c, '*' | '?' | '['))
+            .collect();
+        let sorted = &self.labels().sorted;
+        let start = sorted.partition_point(|word| word.as_str() < prefix.as_str());
+        let words = sorted[start..]
+            .iter()
+            .take_while(|word| word.starts_with(&prefix))
+            .filter(|word| {
+                let word: Vec<char> = word.chars().collect();
+                glob_match(&pattern, &word)
+            });
+        self.entries(words)
+    }
+
+    pub(crate) fn labels(&self) -> &Labels {
+        self.labels.get_or_init(|| Labels::new(self))
+    }
+
+    pub(crate) fn entries<'a, I>(&'a self, words: I) -> Vec<(&'a str, &'a [Rule])>
+    where
+        I: Iterator<Item = &'a String>,
+    {
+        words
+            .filter_map(|word| self.map.get(word).map(|rules| (&word[..], &rules[..])))
+            .collect()
+    }
+}
+
+fn glob_match(pattern: &[char], word: &[char]) -> bool {
+    // where to resume after the last star: (pattern index after it, word index)
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut w) = (0, 0);
+    while w < word.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, w));
+                p += 1;
+                continue;
+            }
+            Some(_) => {
+                if let Some((len, true)) = one(&pattern[p..], word[w]) {
+                    p += len;
+                    w += 1;
+                    continue;
+                }
+            }
+            None => {}
+        }
+        // let the last star swallow one more character
+        match star {
+            Some((after, from)) => {
+                p = after;
+                w = from + 1;
+                star = Some((after, from + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// matches a single character against the start of a glob, returning how much of the glob
+// was used and whether it matched
+fn one(pattern: &[char], c: char) -> Option<(usize, bool)> {
+    match pattern.first()? {
+        '?' => Some((1, true)),
+        '[' => {
+            let negated = pattern.get(1) == Some(&'!');
+            let start = if negated { 2 } else { 1 };
+            // a bracket with no end is just a bracket
+            let end = match pattern[start..].iter().position(|&p| p == ']') {
+                Some(end) => start + end,
+                None => return Some((1, c == '[')),
+            };
+            let found = pattern[start..end].contains(&c);
+            Some((end + 1, found != negated))
+        }
+        &p => Some((1, p == c)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(found: Vec<(&str, &[Rule])>) -> Vec<String> {
+        found.into_iter().map(|(word, _)| word.to_string()).collect()
+    }
+
+    #[test]
+    fn globs() {
+        let matches = |p: &str, w: &str| {
+            glob_match(&p.chars().collect::<Vec<_>>(), &w.chars().collect::<Vec<_>>())
+        };
+        assert!(matches("c*t", "cat"));
+        assert!(matches("c*t", "ct"));
+        assert!(matches("*ion", "nation"));
+        assert!(!matches("*ion", "nations"));
+        assert!(matches("b[aeiou]t", "bit"));
+        assert!(!matches("b[!aeiou]t", "bit"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(!matches("a*b*c", "axxbyy"));
+        assert!(matches("[ab", "[ab"));
+        assert!(matches("??", "ab"));
+        assert!(!matches("??", "abc"));
+    }
+
+    #[test]
+    fn searching() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+
+        let prefixed = words(d.with_prefix("rust"));
+        assert!(prefixed.contains(&"rust".to_string()));
+        assert!(prefixed.contains(&"rustic".to_string()));
+        assert!(prefixed.windows(2).all(|w| w[0] < w[1]));
+
+        let suffixed = words(d.with_suffix("tion"));
+        assert!(suffixed.contains(&"nation".to_string()));
+        assert!(suffixed.iter().all(|w| w.ends_with("tion")));
+        assert!(suffixed.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            suffixed.len(),
+            d.iter().filter(|(word, _)| word.ends_with("tion")).count()
+        );
+
+        let inside = words(d.containing("phon"));
+        assert!(inside.contains(&"telephone".to_string()));
+        assert_eq!(words(d.glob("*tion")), suffixed);
+        assert_eq!(words(d.glob("rust*")), prefixed);
+        assert!(d.with_prefix("qqqq").is_empty());
+        assert_eq!(d.with_prefix("").len(), d.len());
+    }
+}
@@ -0,0 +1,309 @@
+//! Psycholinguistic measures of words and nonwords against the dictionary
+//!
+//! These are the measures usually reported for the stimuli of spoken word recognition
+//! experiments: the phonological neighbours of a pronunciation and the density of its
+//! neighbourhood, the positional probabilities of its segments and biphones, and its
+//! uniqueness point. Stress is ignored throughout, so `AH0` and `AH1` count as the same
+//! segment.
+
+use std::collections::HashMap;
+
+use crate::core::Symbol;
+use crate::Cmudict;
+
+const SYMBOLS: usize = 39;
+
+/// Neighbourhood and phonotactic statistics of a dictionary
+///
+/// Every pronunciation of every word counts towards the statistics with the word's weight,
+/// which is one unless a frequency list is given with `LexicalStats::with_frequencies`.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, LexicalStats};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict: Cmudict = "
+/// cat K AE1 T
+/// bat B AE1 T
+/// cap K AE1 P
+/// at AE1 T
+/// catalog K AE1 T AH0 L AO2 G
+/// dog D AO1 G
+/// ".trim().parse()?;
+/// let stats = LexicalStats::new(&dict);
+///
+/// let cat = dict.get("cat").unwrap()[0].pronunciation();
+/// assert_eq!(stats.neighbours(cat), vec!["at", "bat", "cap"]);
+/// assert_eq!(stats.density(cat), 3.0);
+/// // "cat" is the start of "catalog", so it never becomes unique
+/// assert_eq!(stats.uniqueness_point(cat), None);
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LexicalStats {
+    words: Vec<String>,
+    weights: Vec<f64>,
+    // every distinct pronunciation and the words that have it
+    index: HashMap<Vec<u8>, Vec<usize>>,
+    // the distinct pronunciations in order, for finding everything with a prefix
+    sorted: Vec<Vec<u8>>,
+    // the weight of each segment at each position, and of each biphone starting there
+    segments: Vec<Vec<f64>>,
+    biphones: Vec<HashMap<(u8, u8), f64>>,
+}
+
+impl LexicalStats {
+    /// Gathers statistics from a dictionary, giving every word the same weight
+    pub fn new(dict: &Cmudict) -> LexicalStats {
+        LexicalStats::build(dict, |_| 1.0)
+    }
+
+    /// Gathers statistics from a dictionary, weighting each word by its frequency
+    ///
+    /// Words that aren't in the list have a weight of zero, so they still count as
+    /// neighbours but not towards the weighted density or the probabilities. The weights are
+    /// used as they are, so pass log frequencies to weight by those instead.
+    pub fn with_frequencies<I, S>(dict: &Cmudict, frequencies: I) -> LexicalStats
+    where
+        I: IntoIterator<Item = (S, f64)>,
+        S: AsRef<str>,
+    {
+        let frequencies: HashMap<String, f64> = frequencies
+            .into_iter()
+            .map(|(word, f)| (word.as_ref().to_lowercase(), f))
+            .collect();
+        LexicalStats::build(dict, |word| frequencies.get(word).copied().unwrap_or(0.0))
+    }
+
+    fn build<F: Fn(&str) -> f64>(dict: &Cmudict, weight: F) -> LexicalStats {
+        let mut words: Vec<&str> = dict.iter().map(|(word, _)| word).collect();
+        words.sort_unstable();
+        let mut stats = LexicalStats {
+            words: words.iter().map(|w| w.to_string()).collect(),
+            weights: words.iter().map(|w| weight(w)).collect(),
+            index: HashMap::new(),
+            sorted: Vec::new(),
+            segments: Vec::new(),
+            biphones: Vec::new(),
+        };
+        for (id, word) in words.iter().enumerate() {
+            let w = stats.weights[id];
+            for rule in dict.get(word).unwrap_or(&[]) {
+                let codes = codes(rule.pronunciation());
+                for (pos, &c) in codes.iter().enumerate() {
+                    if stats.segments.len() <= pos {
+                        stats.segments.push(vec![0.0; SYMBOLS]);
+                        stats.biphones.push(HashMap::new());
+                    }
+                    stats.segments[pos][c as usize] += w;
+                    if let Some(&next) = codes.get(pos + 1) {
+                        *stats.biphones[pos].entry((c, next)).or_default() += w;
+                    }
+                }
+                let owners = stats.index.entry(codes).or_default();
+                if !owners.contains(&id) {
+                    owners.push(id);
+                }
+            }
+        }
+        stats.sorted = stats.index.keys().cloned().collect();
+        stats.sorted.sort_unstable();
+        stats
+    }
+
+    /// Finds the words whose pronunciation is one substitution, insertion or deletion away
+    ///
+    /// Words that can sound exactly the same aren't neighbours, even if another of their
+    /// pronunciations is one change away, so a word with several pronunciations is never its
+    /// own neighbour. The words are in alphabetical order.
+    pub fn neighbours(&self, phones: &[Symbol]) -> Vec<&str> {
+        let ids = self.neighbour_ids(&codes(phones));
+        ids.into_iter().map(|id| &self.words[id][..]).collect()
+    }
+
+    /// Returns the number of neighbours, or the total weight of the neighbours if the
+    /// statistics were gathered with a frequency list
+    pub fn density(&self, phones: &[Symbol]) -> f64 {
+        let ids = self.neighbour_ids(&codes(phones));
+        ids.into_iter().map(|id| self.weights[id]).sum()
+    }
+
+    /// Returns the probability of each segment occurring at its position in a word
+    ///
+    /// The probability is the weight of the pronunciations with that segment at that
+    /// position over the weight of all the pronunciations long enough to have one there.
+    pub fn segment_probabilities(&self, phones: &[Symbol]) -> Vec<f64> {
+        codes(phones)
+            .iter()
+            .enumerate()
+            .map(|(pos, &c)| match self.segments.get(pos) {
+                Some(counts) => ratio(counts[c as usize], counts.iter().sum()),
+                None => 0.0,
+            })
+            .collect()
+    }
+
+    /// Returns the probability of each pair of adjacent segments occurring at its position
+    ///
+    /// The first probability is for the first and second segments, so there is one fewer
+    /// than there are segments.
+    pub fn biphone_probabilities(&self, phones: &[Symbol]) -> Vec<f64> {
+        codes(phones)
+            .windows(2)
+            .enumerate()
+            .map(|(pos, pair)| match self.biphones.get(pos) {
+                Some(counts) => ratio(
+                    counts.get(&(pair[0], pair[1])).copied().unwrap_or(0.0),
+                    counts.values().sum(),
+                ),
+                None => 0.0,
+            })
+            .collect()
+    }
+
+    /// Returns how many segments have to be heard before a pronunciation can't be the start
+    /// of any other in the dictionary
+    ///
+    /// A pronunciation that isn't in the dictionary has its uniqueness point where it stops
+    /// being the start of any word at all. If the whole pronunciation is the start of some
+    /// other word, like "cat" is of "catalog", there is no uniqueness point.
+    pub fn uniqueness_point(&self, phones: &[Symbol]) -> Option<usize> {
+        let codes = codes(phones);
+        (1..=codes.len()).find(|&len| {
+            let prefix = &codes[..len];
+            let start = self.sorted.partition_point(|p| p.as_slice() < prefix);
+            self.sorted[start..]
+                .iter()
+                .take_while(|p| p.starts_with(prefix))
+                .all(|p| *p == codes)
+        })
+    }
+
+    // the words one edit away that can't sound exactly the same, in order
+    fn neighbour_ids(&self, codes: &[u8]) -> Vec<usize> {
+        let mut ids = Vec::new();
+        let mut candidate = Vec::with_capacity(codes.len() + 1);
+        let mut check = |candidate: &Vec<u8>| {
+            if let Some(owners) = self.index.get(candidate) {
+                ids.extend(owners.iter().copied());
+            }
+        };
+        for pos in 0..=codes.len() {
+            // insertions before `pos`
+            for c in 0..SYMBOLS as u8 {
+                candidate.clear();
+                candidate.extend_from_slice(&codes[..pos]);
+                candidate.push(c);
+                candidate.extend_from_slice(&codes[pos..]);
+                check(&candidate);
+            }
+            if pos == codes.len() {
+                break;
+            }
+            // deletion and substitutions at `pos`
+            candidate.clear();
+            candidate.extend_from_slice(&codes[..pos]);
+            candidate.extend_from_slice(&codes[pos + 1..]);
+            check(&candidate);
+            for c in (0..SYMBOLS as u8).filter(|&c| c != codes[pos]) {
+                candidate.clear();
+                candidate.extend_from_slice(codes);
+                candidate[pos] = c;
+                check(&candidate);
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        // words with a pronunciation one change away may also sound exactly the same
+        if let Some(homophones) = self.index.get(codes) {
+            ids.retain(|id| !homophones.contains(id));
+        }
+        ids
+    }
+}
+
+fn codes(phones: &[Symbol]) -> Vec<u8> {
+    phones.iter().map(|s| s.index() as u8).collect()
+}
+
+fn ratio(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Stress;
+    use crate::core::Symbol::*;
+
+    #[test]
+    fn neighbourhoods() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let stats = LexicalStats::new(&d);
+        let p = |word: &str| d.get(word).unwrap()[0].pronunciation();
+
+        let cat = stats.neighbours(p("cat"));
+        for word in &["bat", "cap", "at", "scat", "cut", "kit"] {
+            assert!(cat.contains(word), "{} isn't a neighbour of cat", word);
+        }
+        assert!(!cat.contains(&"cat"));
+        assert!(!cat.contains(&"kat"));
+        assert_eq!(stats.density(p("cat")), cat.len() as f64);
+        assert!(stats.density(p("cat")) > stats.density(p("elephant")));
+
+        // a nonword works too
+        let blick = [B, L, IH(Stress::Primary), K];
+        assert!(stats.neighbours(&blick).contains(&"brick"));
+
+        // "tomato" is one change away from its other pronunciation
+        let tomato = stats.neighbours(p("tomato"));
+        assert!(!tomato.contains(&"tomato"));
+        assert_eq!(stats.density(p("tomato")), tomato.len() as f64);
+        for word in &["the", "either"] {
+            assert!(!stats.neighbours(p(word)).contains(word), "{} is its own neighbour", word);
+        }
+
+        let weighted = LexicalStats::with_frequencies(&d, vec![("bat", 10.0), ("cap", 5.0)]);
+        assert_eq!(weighted.density(p("cat")), 15.0);
+        assert_eq!(weighted.neighbours(p("cat")), cat);
+    }
+
+    #[test]
+    fn probabilities() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let stats = LexicalStats::new(&d);
+
+        let segments = stats.segment_probabilities(&[S, T, AE(Stress::Primary), K]);
+        assert_eq!(segments.len(), 4);
+        assert!(segments.iter().all(|&p| p > 0.0 && p < 1.0));
+        // words start with S much more often than with ZH
+        assert!(segments[0] > stats.segment_probabilities(&[ZH])[0]);
+
+        let biphones = stats.biphone_probabilities(&[S, T, AE(Stress::Primary), K]);
+        assert_eq!(biphones.len(), 3);
+        assert_eq!(stats.biphone_probabilities(&[NG, NG]), vec![0.0]);
+
+        let p = |word: &str| d.get(word).unwrap()[0].pronunciation();
+        assert_eq!(stats.uniqueness_point(p("cat")), None);
+        // "wisdom" can't be anything else by its fifth segment
+        assert_eq!(stats.uniqueness_point(p("wisdom")), Some(5));
+        // no word starts with ZH ZH
+        assert_eq!(stats.uniqueness_point(&[ZH, ZH, ZH]), Some(2));
+    }
+}
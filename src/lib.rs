@@ -2,27 +2,95 @@
 #![deny(missing_docs)]
 
 use std::{io::Cursor, fs::File};
+use std::borrow::Cow;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::OnceLock;
 use std::{collections::HashMap};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
+mod acronym;
+mod allophone;
+mod align;
+mod compound;
+mod alphabet;
 mod core;
+mod dialect;
+mod distance;
 mod errors;
+mod features;
+mod g2p;
+mod ipa;
+mod joint;
+mod labels;
+mod lexical;
+mod lookup;
+mod morphology;
+mod nearest;
+mod normalize;
+mod p2g;
+mod pairs;
+mod pattern;
+mod phonotactics;
+mod pseudoword;
+mod suggest;
+mod syllable;
+mod text;
+mod timit;
+mod variant;
 
+pub use acronym::{Acronyms, Reading};
+pub use allophone::{AllophoneRule, Allophones};
+pub use align::Aligner;
+pub use alphabet::{Arpabet, Kirshenbaum, PhoneticAlphabet, XSampa};
+pub use compound::Segmentation;
 pub use crate::core::{Rule, Stress, Symbol};
+pub use dialect::{Dialect, Rewrite};
+pub use distance::{Alignment, Edit, PhoneticDistance};
 pub use errors::{Error, ParseError, ParseResult, Result};
+pub use features::{
+    Backness, ConsonantFeatures, Features, Height, Manner, Place, Voicing, VowelFeatures,
+};
+pub use g2p::{ContextModel, LetterRules, Predictor};
+pub use ipa::{parse_ipa, to_ipa, IpaStyle};
+pub use joint::{evaluate, split, Evaluation, JointModel, MAX_ORDER};
+pub use lexical::LexicalStats;
+pub use lookup::{Lookup, Source};
+pub use nearest::{Neighbour, PronunciationIndex};
+pub use normalize::{verbalize, EnglishHints, Hint, Hints};
+pub use p2g::{Speller, Spelling};
+pub use pairs::{MinimalPair, PairFilter, Position};
+pub use pattern::Pattern;
+pub use phonotactics::{Part, Phonotactics, Violation};
+pub use pseudoword::{Pseudoword, Pseudowords};
+pub use suggest::Suggestion;
+pub use syllable::{syllabify, Syllable};
+pub use text::{Boundary, Transcriber, Transcription, Word};
+pub use timit::{collapse, ExtendedRule, Phone};
+pub use variant::{Policy, Weights};
+
+use labels::Labels;
 
 /// A dictionary containing words & their pronunciations
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cmudict {
     map: HashMap<String, Vec<Rule>>,
+    // built the first time the words are searched by spelling
+    #[cfg_attr(feature = "serde", serde(skip))]
+    labels: OnceLock<Labels>,
 }
 
 impl Cmudict {
+    fn from_map(map: HashMap<String, Vec<Rule>>) -> Cmudict {
+        Cmudict {
+            map,
+            labels: OnceLock::new(),
+        }
+    }
+
     /// Takes a path to a cmudict file and tries to construct a `Cmudict` struct
     ///
     /// # Example
@@ -48,7 +116,54 @@ impl Cmudict {
         let file = File::open(dict)?; 
         let buf = BufReader::new(file);
         let map = make_mapping(buf)?;
-        Ok(Cmudict { map })
+        Ok(Cmudict::from_map(map))
+    }
+
+    /// Takes a path to a dictionary written with TIMIT phones and tries to construct a
+    /// `Cmudict` from it
+    ///
+    /// The phones are converted to CMU symbols with `collapse`. Lines are read the same way
+    /// as `Cmudict::new` reads them, except that comments may also start with a single `;`,
+    /// a `#` only starts a comment at the start of a word so the `h#` phone can be read, the
+    /// phones may be wrapped in slashes as they are in TIMITDIC, and a part of speech after a
+    /// `~` in the label is ignored.
+    pub fn new_extended<P: AsRef<Path>>(dict: P) -> Result<Cmudict> {
+        let file = File::open(dict)?;
+        let map = make_extended_mapping(BufReader::new(file))?;
+        Ok(Cmudict::from_map(map))
+    }
+
+    /// Parses a dictionary written with TIMIT phones, like `Cmudict::new_extended`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, Symbol, Stress};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::parse_extended("
+    /// ; from TIMITDIC
+    /// butter  /b ah1 dx axr/
+    /// button  /b ah1 tcl en/
+    /// ".trim())?;
+    /// assert_eq!(
+    ///     dict.get("button").unwrap()[0].pronunciation(),
+    ///     &[Symbol::B, Symbol::AH(Stress::Primary), Symbol::T, Symbol::AH(Stress::None), Symbol::N]
+    /// );
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn parse_extended(s: &str) -> Result<Cmudict> {
+        let map = make_extended_mapping(Cursor::new(s))?;
+        Ok(Cmudict::from_map(map))
     }
 
     /// Look for a word in the dictionary, and retrieve it's pronunciation
@@ -84,6 +199,73 @@ impl Cmudict {
     pub fn get(&self, s: &str) -> Option<&[Rule]> {
         self.map.get(s).map(|r| &r[..])
     }
+
+    /// Look for a word in the dictionary, guessing its pronunciation with `predictor` if it
+    /// isn't there
+    ///
+    /// A word that isn't in the dictionary but is an inflected or prefixed form of one that
+    /// is, like "kumquats", has its pronunciation derived with `Cmudict::derive` rather than
+    /// guessed, and so does a compound of words that are, like "sunflowerseed", with
+    /// `Cmudict::decompose`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, LetterRules, Source};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let rules = LetterRules::english();
+    ///
+    /// assert_eq!(dict.get_or_predict("rust", &rules).source(), Source::Dictionary);
+    /// assert_eq!(dict.get_or_predict("rustacean", &rules).source(), Source::Predicted);
+    /// assert_eq!(dict.get_or_predict("kumquats", &rules).source(), Source::Derived);
+    /// assert_eq!(dict.get_or_predict("sunflowerseed", &rules).source(), Source::Derived);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn get_or_predict<P>(&self, word: &str, predictor: &P) -> Lookup<'_>
+    where
+        P: Predictor + ?Sized,
+    {
+        if let Some(rules) = self.get(word) {
+            return Lookup::new(Cow::Borrowed(rules), Source::Dictionary);
+        }
+        let derived = self.derive(word);
+        if !derived.is_empty() {
+            return Lookup::new(Cow::Owned(derived), Source::Derived);
+        }
+        let compounds: Vec<Rule> =
+            self.decompose(word).into_iter().map(Segmentation::into_rule).collect();
+        if !compounds.is_empty() {
+            return Lookup::new(Cow::Owned(compounds), Source::Derived);
+        }
+        let rule = Rule::new(word, predictor.predict(word));
+        Lookup::new(Cow::Owned(vec![rule]), Source::Predicted)
+    }
+
+    /// Iterates over every word in the dictionary along with its pronunciations
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Rule])> {
+        self.map.iter().map(|(label, rules)| (&label[..], &rules[..]))
+    }
+
+    /// Returns the number of words in the dictionary
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the dictionary has no words in it
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
 }
 
 impl FromStr for Cmudict {
@@ -91,9 +273,7 @@ impl FromStr for Cmudict {
 
     fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
        let cursor = Cursor::new(s);
-       Ok(Self {
-           map: make_mapping(cursor)?,
-       })
+       Ok(Cmudict::from_map(make_mapping(cursor)?))
     }
 }
 
@@ -126,6 +306,7 @@ fn left(s: &str) -> &str {
     parts.next().unwrap()
 }
 
+#[allow(clippy::needless_splitn, clippy::unnecessary_lazy_evaluations, clippy::redundant_closure)]
 fn make_mapping<R: BufRead>(reader: R) -> Result<HashMap<String, Vec<Rule>>> {
     let mut map = HashMap::new();
     for (idx, line) in reader.lines().enumerate() {
@@ -145,6 +326,35 @@ fn make_mapping<R: BufRead>(reader: R) -> Result<HashMap<String, Vec<Rule>>> {
     Ok(map)
 }
 
+// cuts a line of TIMIT phones at a comment, which starts with a `#` at the start of a word,
+// so the `#` of the `h#` phone is kept
+fn uncomment(s: &str) -> &str {
+    let mut previous = ' ';
+    for (idx, c) in s.char_indices() {
+        if c == '#' && previous.is_whitespace() {
+            return &s[..idx];
+        }
+        previous = c;
+    }
+    s
+}
+
+fn make_extended_mapping<R: BufRead>(reader: R) -> Result<HashMap<String, Vec<Rule>>> {
+    let mut map = HashMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = uncomment(&line);
+        if line.starts_with(';') || line.trim().is_empty() {
+            continue;
+        }
+        let rule = ExtendedRule::from_str(line)?.to_rule();
+        let label = rule.label().split('~').next().unwrap_or_default();
+        let label = split_label(label).to_string();
+        map.entry(label).or_insert_with(Vec::new).push(rule);
+    }
+    Ok(map)
+}
+
 fn split_label(s: &str) -> &str {
     let mut parts = s.rsplitn(2, '(');
     let _ = parts.next();
@@ -155,6 +365,34 @@ fn split_label(s: &str) -> &str {
     }
 }
 
+// the trimmed lines of a list of rules or readings with their indices, skipping blank lines
+// and lines starting with `;;`
+fn entry_lines<R: BufRead>(reader: R) -> impl Iterator<Item = Result<(usize, String)>> {
+    reader.lines().enumerate().filter_map(|(idx, line)| match line {
+        Ok(line) => {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(";;") {
+                None
+            } else {
+                Some(Ok((idx, line.to_string())))
+            }
+        }
+        Err(e) => Some(Err(e.into())),
+    })
+}
+
+// the pronunciations of the words starting with any of `prefixes`, for tests to train models
+// on, since the whole dictionary takes a while to train on without optimisations
+#[cfg(test)]
+pub(crate) fn sample<'a>(
+    dict: &'a Cmudict,
+    prefixes: &'a [&str],
+) -> impl Iterator<Item = (&'a str, &'a [Symbol])> {
+    dict.iter()
+        .filter(move |(word, _)| prefixes.iter().any(|p| word.starts_with(p)))
+        .flat_map(|(word, rules)| rules.iter().map(move |r| (word, r.pronunciation())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::core::{Rule, Stress, Symbol};
@@ -233,6 +471,40 @@ mod tests {
     }
 
     #[test]
+    fn predicting() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+
+        let apple = d.get_or_predict("apple", &rules);
+        assert!(apple.is_found());
+        assert_eq!(apple.rules(), d.get("apple").unwrap());
+
+        let blonk = d.get_or_predict("blonk", &rules);
+        assert_eq!(blonk.source(), Source::Predicted);
+        assert_eq!(
+            blonk.into_rules(),
+            vec![Rule::new(
+                "blonk",
+                vec![Symbol::B, Symbol::L, Symbol::AH(Stress::Primary), Symbol::NG, Symbol::K]
+            )]
+        );
+    }
+
+    #[test]
+    fn extended() {
+        let d = Cmudict::parse_extended(
+            "# silences are kept\nbutter /h# bcl b ah1 dx axr h#/ # with a flap\n;comment\n",
+        )
+        .unwrap();
+        assert_eq!(d.len(), 1);
+        assert_eq!(
+            d.get("butter").unwrap()[0].pronunciation(),
+            &[Symbol::B, Symbol::AH(Stress::Primary), Symbol::T, Symbol::ER(Stress::None)]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::needless_range_loop, clippy::needless_borrow)]
     fn threads() {
         let d =
             Arc::new(Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict"));
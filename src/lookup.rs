@@ -0,0 +1,54 @@
+//! Results of lookups that may fall back to something other than the dictionary
+
+use std::borrow::Cow;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::Rule;
+
+/// Where the pronunciations returned by a lookup came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Source {
+    /// The word was found in the dictionary
+    Dictionary,
+    /// The word wasn't in the dictionary, so its pronunciation was guessed
+    Predicted,
+    /// The word wasn't in the dictionary, but its pronunciation was built from the
+    /// pronunciations of words that are
+    Derived,
+}
+
+/// The pronunciations of a word, tagged with where they came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lookup<'a> {
+    rules: Cow<'a, [Rule]>,
+    source: Source,
+}
+
+impl<'a> Lookup<'a> {
+    pub(crate) fn new(rules: Cow<'a, [Rule]>, source: Source) -> Lookup<'a> {
+        Lookup { rules, source }
+    }
+
+    /// Returns every pronunciation that was found or guessed
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Returns where the pronunciations came from
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Returns `true` if the word was found in the dictionary
+    pub fn is_found(&self) -> bool {
+        self.source == Source::Dictionary
+    }
+
+    /// Consumes the lookup, returning the pronunciations it holds
+    pub fn into_rules(self) -> Vec<Rule> {
+        self.rules.into_owned()
+    }
+}
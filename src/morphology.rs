@@ -0,0 +1,234 @@
+//! Deriving the pronunciations of inflected and prefixed words from their stems
+//!
+//! The dictionary has most common words, but not every plural, past tense or possessive of
+//! the rarer ones. Their pronunciations can be built from the stem's: the endings are
+//! regular, and which form an ending takes only depends on the last sound of the stem, like
+//! the `Z` of "dogs" against the `S` of "cats" and the `IH0 Z` of "horses".
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::Cmudict;
+
+// how many affixes can be taken off a word to find a stem in the dictionary
+const MAX_AFFIXES: usize = 2;
+
+// the shortest stem an affix is taken off
+const MIN_STEM: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ending {
+    // plurals, possessives and the third person
+    S,
+    // past tenses and participles
+    Ed,
+    Ing,
+    Er,
+    Ly,
+}
+
+// (suffix, what the stem ends with in its place, ending)
+const SUFFIXES: &[(&str, &str, Ending)] = &[
+    ("'s", "", Ending::S),
+    ("s", "", Ending::S),
+    ("es", "", Ending::S),
+    ("ies", "y", Ending::S),
+    ("ed", "", Ending::Ed),
+    ("d", "", Ending::Ed),
+    ("ied", "y", Ending::Ed),
+    ("ing", "", Ending::Ing),
+    ("ing", "e", Ending::Ing),
+    ("ying", "ie", Ending::Ing),
+    ("er", "", Ending::Er),
+    ("r", "", Ending::Er),
+    ("ier", "y", Ending::Er),
+    ("ly", "", Ending::Ly),
+    ("ily", "y", Ending::Ly),
+    ("ly", "le", Ending::Ly),
+    ("ly", "l", Ending::Ly),
+];
+
+// (prefix, its pronunciation)
+const PREFIXES: &[(&str, [Symbol; 2])] = &[
+    ("un", [Symbol::AH(Stress::None), Symbol::N]),
+    ("re", [Symbol::R, Symbol::IY(Stress::None)]),
+];
+
+impl Cmudict {
+    /// Derives pronunciations for a word from the pronunciations of its stem
+    ///
+    /// The word is taken to be a stem in the dictionary with an ending or a prefix added:
+    /// `-s`, `-es` and `'s`, which become `S`, `Z` or `IH0 Z`; `-ed`, which becomes `T`, `D`
+    /// or `IH0 D`; `-ing`, `-er` and `-ly`; and `un-` and `re-`. Spelling changes like the
+    /// doubled consonant of "stopped" and the `y` of "carried" are undone to find the stem.
+    /// Up to two affixes can be taken off, so "unlocked" can be found from "lock".
+    ///
+    /// Every pronunciation that can be derived is returned, whether or not the word is in the
+    /// dictionary, with those from the more likely stems first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, Stress, Symbol};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// assert!(dict.get("kumquats").is_none());
+    ///
+    /// let kumquats = dict.derive("kumquats");
+    /// let mut expected = dict.get("kumquat").unwrap()[0].pronunciation().to_vec();
+    /// expected.push(Symbol::S);
+    /// assert_eq!(kumquats[0].pronunciation(), &expected[..]);
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn derive(&self, word: &str) -> Vec<Rule> {
+        let word = word.to_lowercase();
+        let mut rules: Vec<Rule> = Vec::new();
+        for phones in self.derivations(&word, MAX_AFFIXES) {
+            if !rules.iter().any(|rule| rule.pronunciation() == &phones[..]) {
+                rules.push(Rule::new(word.as_str(), phones));
+            }
+        }
+        rules
+    }
+
+    fn derivations(&self, word: &str, affixes: usize) -> Vec<Vec<Symbol>> {
+        let mut found = Vec::new();
+        if affixes == 0 {
+            return found;
+        }
+        for &(suffix, replacement, ending) in SUFFIXES {
+            let base = match word.strip_suffix(suffix) {
+                Some(base) if base.len() >= MIN_STEM => base,
+                _ => continue,
+            };
+            let mut stems = vec![format!("{}{}", base, replacement)];
+            // "stopped" and "stopping" have the "p" of "stop" doubled
+            let doubles = matches!(ending, Ending::Ed | Ending::Ing | Ending::Er);
+            let mut letters = base.chars().rev();
+            if let (Some(a), Some(b)) = (letters.next(), letters.next()) {
+                if doubles && replacement.is_empty() && a == b && !"aeiou".contains(a) {
+                    stems.push(base[..base.len() - a.len_utf8()].to_string());
+                }
+            }
+            for stem in stems {
+                for phones in self.stems(&stem, affixes - 1) {
+                    if let Some(derived) = add_ending(phones, ending, replacement) {
+                        found.push(derived);
+                    }
+                }
+            }
+        }
+        for &(prefix, added) in PREFIXES {
+            let stem = match word.strip_prefix(prefix) {
+                Some(stem) if stem.len() >= MIN_STEM => stem,
+                _ => continue,
+            };
+            for phones in self.stems(stem, affixes - 1) {
+                found.push(added.iter().copied().chain(phones).collect());
+            }
+        }
+        found
+    }
+
+    // the pronunciations of a stem, from the dictionary or derived from a shorter stem
+    fn stems(&self, stem: &str, affixes: usize) -> Vec<Vec<Symbol>> {
+        match self.get(stem) {
+            Some(rules) => rules.iter().map(|r| r.pronunciation().to_vec()).collect(),
+            None => self.derivations(stem, affixes),
+        }
+    }
+}
+
+fn add_ending(mut phones: Vec<Symbol>, ending: Ending, replacement: &str) -> Option<Vec<Symbol>> {
+    let last = *phones.last()?;
+    match ending {
+        Ending::S => phones.extend(s_suffix(last)),
+        Ending::Ed => match last {
+            Symbol::T | Symbol::D => phones.extend(&[Symbol::IH(Stress::None), Symbol::D]),
+            _ if last.is_voiced() => phones.push(Symbol::D),
+            _ => phones.push(Symbol::T),
+        },
+        Ending::Ing => phones.extend(&[Symbol::IH(Stress::None), Symbol::NG]),
+        Ending::Er => phones.push(Symbol::ER(Stress::None)),
+        Ending::Ly => {
+            if last == Symbol::IY(Stress::None) && replacement == "y" {
+                // "happy" becomes "happily"
+                phones.pop();
+                phones.extend(&[Symbol::AH(Stress::None), Symbol::L, Symbol::IY(Stress::None)]);
+            } else if last == Symbol::L {
+                // "gentle" becomes "gently", and "full" becomes "fully"
+                phones.push(Symbol::IY(Stress::None));
+            } else if replacement.is_empty() {
+                phones.extend(&[Symbol::L, Symbol::IY(Stress::None)]);
+            } else {
+                return None;
+            }
+        }
+    }
+    Some(phones)
+}
+
+// the ending of a plural, possessive or third person verb after `last`
+pub(crate) fn s_suffix(last: Symbol) -> Vec<Symbol> {
+    use Symbol::*;
+    match last {
+        S | Z | SH | ZH | CH | JH => vec![IH(Stress::None), Z],
+        _ if last.is_voiced() => vec![Z],
+        _ => vec![S],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflections() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        // every one of these is in the dictionary, and can be derived from its stem
+        let words = [
+            "cats", "dogs", "horses", "boxes", "cities", "john's", "walked", "played",
+            "wanted", "baked", "stopped", "carried", "walking", "baking", "stopping", "dying",
+            "walker", "baker", "runner", "happier", "quickly", "happily", "gently", "fully",
+            "unhappy", "redo", "unlocked",
+        ];
+        for word in &words {
+            let derived = d.derive(word);
+            let expected = d.get(word).unwrap();
+            let matches =
+                |rule: &Rule| expected.iter().any(|e| e.pronunciation() == rule.pronunciation());
+            assert!(
+                derived.iter().any(matches),
+                "{} was derived as {:?}",
+                word,
+                derived
+            );
+            assert!(derived.iter().all(|rule| rule.label() == *word));
+        }
+        assert!(d.derive("xq").is_empty());
+        assert!(d.derive("zzzzing").is_empty());
+    }
+
+    #[test]
+    fn endings() {
+        use Symbol::*;
+        assert_eq!(s_suffix(T), vec![S]);
+        assert_eq!(s_suffix(G), vec![Z]);
+        assert_eq!(s_suffix(IY(Stress::Primary)), vec![Z]);
+        assert_eq!(s_suffix(CH), vec![IH(Stress::None), Z]);
+
+        let ed = |last: Symbol| add_ending(vec![last], Ending::Ed, "").unwrap()[1..].to_vec();
+        assert_eq!(ed(K), vec![T]);
+        assert_eq!(ed(N), vec![D]);
+        assert_eq!(ed(T), vec![IH(Stress::None), D]);
+    }
+}
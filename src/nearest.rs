@@ -0,0 +1,350 @@
+//! Searching the dictionary for the pronunciations closest to a sequence of symbols
+//!
+//! Every distinct pronunciation is put into a BK-tree under the weighted edit distance of
+//! `PhoneticDistance`. The default weights make that distance a metric, so a query only has to
+//! visit the parts of the tree that could hold something closer than what it has already
+//! found, rather than every pronunciation in the dictionary.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::distance::PhoneticDistance;
+use crate::Cmudict;
+
+// every symbol with every stress
+const CODES: usize = 39 * 3;
+// distances are bucketed into steps of 1 / SCALE between a node and its children
+const SCALE: f64 = 20.0;
+const EPSILON: f64 = 1e-9;
+
+/// A word found by a `PronunciationIndex`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbour<'a> {
+    word: &'a str,
+    rule: &'a Rule,
+    distance: f64,
+}
+
+impl<'a> Neighbour<'a> {
+    /// Returns the word, without any variant marker
+    pub fn word(&self) -> &'a str {
+        self.word
+    }
+
+    /// Returns the pronunciation that was found
+    pub fn rule(&self) -> &'a Rule {
+        self.rule
+    }
+
+    /// Returns the distance of the pronunciation from the query
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+}
+
+/// An index of every pronunciation in a dictionary for finding the ones closest to a query
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, PronunciationIndex, Stress, Symbol};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict: Cmudict = "
+/// cat K AE1 T
+/// bat B AE1 T
+/// pat P AE1 T
+/// dog D AO1 G
+/// ".trim().parse()?;
+/// let index = PronunciationIndex::new(&dict);
+///
+/// // "bad", which isn't in the dictionary
+/// let bad = [Symbol::B, Symbol::AE(Stress::Primary), Symbol::D];
+/// let nearest = index.nearest(&bad, 2);
+/// assert_eq!(nearest[0].word(), "bat");
+/// assert_eq!(nearest[1].word(), "pat");
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PronunciationIndex {
+    weights: PhoneticDistance,
+    // substitution costs between every pair of codes
+    costs: Vec<f64>,
+    entries: Vec<(String, Rule)>,
+    // the distinct pronunciations, the entries that have each one, and the tree over them
+    keys: Vec<Vec<u8>>,
+    owners: Vec<Vec<usize>>,
+    children: Vec<Vec<(u32, usize)>>,
+}
+
+impl PronunciationIndex {
+    /// Indexes every pronunciation in a dictionary using the default weights
+    pub fn new(dict: &Cmudict) -> PronunciationIndex {
+        PronunciationIndex::with_weights(dict, PhoneticDistance::default())
+    }
+
+    /// Indexes every pronunciation in a dictionary using the given weights
+    ///
+    /// Results are only guaranteed to be the closest if the weights make a metric: the
+    /// insertion and deletion costs are equal, and no substitution costs more than going
+    /// through a third symbol. The default weights do.
+    pub fn with_weights(dict: &Cmudict, weights: PhoneticDistance) -> PronunciationIndex {
+        let mut symbols = vec![Symbol::AA(Stress::None); CODES];
+        for &symbol in crate::alphabet::ALL.iter() {
+            for &stress in &[Stress::None, Stress::Primary, Stress::Secondary] {
+                let symbol = symbol.with_stress(stress);
+                symbols[code(symbol) as usize] = symbol;
+            }
+        }
+        let costs = symbols
+            .iter()
+            .flat_map(|&a| symbols.iter().map(move |&b| (a, b)))
+            .map(|(a, b)| weights.substitution(a, b))
+            .collect();
+
+        // sorted so the tree is the same every time
+        let mut entries: Vec<(String, Rule)> = dict
+            .iter()
+            .flat_map(|(word, rules)| rules.iter().map(move |r| (word.to_string(), r.clone())))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut index = PronunciationIndex {
+            weights,
+            costs,
+            entries: Vec::new(),
+            keys: Vec::new(),
+            owners: Vec::new(),
+            children: Vec::new(),
+        };
+        let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+        for (idx, (_, rule)) in entries.iter().enumerate() {
+            let key: Vec<u8> = rule.pronunciation().iter().map(|&s| code(s)).collect();
+            match seen.get(&key) {
+                Some(&node) => index.owners[node].push(idx),
+                None => {
+                    seen.insert(key.clone(), index.keys.len());
+                    index.insert(key, idx);
+                }
+            }
+        }
+        index.entries = entries;
+        index
+    }
+
+    /// Returns the number of pronunciations in the index
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index has no pronunciations
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Finds the `k` pronunciations closest to `phones`
+    ///
+    /// The results are ordered by their distance, and then by word.
+    pub fn nearest(&self, phones: &[Symbol], k: usize) -> Vec<Neighbour<'_>> {
+        if k == 0 {
+            return Vec::new();
+        }
+        self.search(phones, |found: &[(f64, usize)]| {
+            // the distance needed to have k entries so far
+            let mut count = 0;
+            for &(distance, node) in found {
+                count += self.owners[node].len();
+                if count >= k {
+                    return distance;
+                }
+            }
+            f64::INFINITY
+        }, k)
+    }
+
+    /// Finds every pronunciation no further than `max_distance` from `phones`
+    ///
+    /// The results are ordered by their distance, and then by word.
+    pub fn within(&self, phones: &[Symbol], max_distance: f64) -> Vec<Neighbour<'_>> {
+        self.search(phones, |_: &[(f64, usize)]| max_distance, usize::MAX)
+    }
+
+    /// Returns the distance between two pronunciations with the index's weights
+    pub fn distance(&self, a: &[Symbol], b: &[Symbol]) -> f64 {
+        self.weights.distance(a, b)
+    }
+
+    fn search<F>(&self, phones: &[Symbol], radius: F, limit: usize) -> Vec<Neighbour<'_>>
+    where
+        F: Fn(&[(f64, usize)]) -> f64,
+    {
+        let query: Vec<u8> = phones.iter().map(|&s| code(s)).collect();
+        // (distance, node), kept sorted
+        let mut found: Vec<(f64, usize)> = Vec::new();
+        let mut r = radius(&found);
+        // the subtrees still to search, closest first by the least distance anything in them
+        // could be from the query, in millionths
+        let mut queue = BinaryHeap::new();
+        if !self.keys.is_empty() {
+            queue.push(Reverse((0u64, 0usize)));
+        }
+        while let Some(Reverse((bound, node))) = queue.pop() {
+            let bound = bound as f64 / 1e6;
+            if bound > r + EPSILON {
+                break;
+            }
+            // nothing further than this can be found here or under any child
+            let furthest = self.children[node]
+                .iter()
+                .map(|&(bucket, _)| f64::from(bucket + 1) / SCALE)
+                .fold(0.0, f64::max);
+            let d = match self.between(&query, &self.keys[node], r + furthest + EPSILON) {
+                Some(d) => d,
+                None => continue,
+            };
+            if d <= r + EPSILON {
+                let at = found.partition_point(|&(e, _)| e <= d);
+                found.insert(at, (d, node));
+                r = radius(&found);
+                if r.is_finite() {
+                    found.retain(|&(e, _)| e <= r + EPSILON);
+                }
+            }
+            for &(bucket, child) in &self.children[node] {
+                // everything under the child is within its bucket's distance of this node
+                let low = f64::from(bucket) / SCALE;
+                let high = f64::from(bucket + 1) / SCALE;
+                let child_bound = bound.max(low - d).max(d - high).max(0.0);
+                if child_bound <= r + EPSILON {
+                    queue.push(Reverse(((child_bound * 1e6) as u64, child)));
+                }
+            }
+        }
+
+        let mut neighbours: Vec<Neighbour<'_>> = found
+            .into_iter()
+            .flat_map(|(distance, node)| {
+                self.owners[node].iter().map(move |&idx| {
+                    let (word, rule) = &self.entries[idx];
+                    Neighbour {
+                        word,
+                        rule,
+                        distance,
+                    }
+                })
+            })
+            .collect();
+        neighbours.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap()
+                .then_with(|| a.word.cmp(b.word))
+                .then_with(|| a.rule.label().cmp(b.rule.label()))
+        });
+        neighbours.truncate(limit);
+        neighbours
+    }
+
+    fn insert(&mut self, key: Vec<u8>, owner: usize) {
+        let id = self.keys.len();
+        if id > 0 {
+            let mut node = 0;
+            loop {
+                let distance = self.between(&key, &self.keys[node], f64::INFINITY);
+                let bucket = (distance.unwrap_or_default() * SCALE).floor() as u32;
+                match self.children[node].iter().find(|&&(b, _)| b == bucket) {
+                    Some(&(_, child)) => node = child,
+                    None => {
+                        self.children[node].push((bucket, id));
+                        break;
+                    }
+                }
+            }
+        }
+        self.keys.push(key);
+        self.owners.push(vec![owner]);
+        self.children.push(Vec::new());
+    }
+
+    // the weighted edit distance between two coded pronunciations, or `None` as soon as it's
+    // sure to be more than `bound`
+    fn between(&self, a: &[u8], b: &[u8], bound: f64) -> Option<f64> {
+        let (insertion, deletion) = (self.weights.insertion, self.weights.deletion);
+        let mut row: Vec<f64> = (0..=b.len()).map(|j| j as f64 * insertion).collect();
+        for (i, &x) in a.iter().enumerate() {
+            let costs = &self.costs[x as usize * CODES..(x as usize + 1) * CODES];
+            let mut diagonal = row[0];
+            row[0] = (i + 1) as f64 * deletion;
+            for (j, &y) in b.iter().enumerate() {
+                let cost = (diagonal + costs[y as usize])
+                    .min(row[j + 1] + deletion)
+                    .min(row[j] + insertion);
+                diagonal = row[j + 1];
+                row[j + 1] = cost;
+            }
+            if row.iter().all(|&c| c > bound) {
+                return None;
+            }
+        }
+        Some(row[b.len()])
+    }
+}
+
+fn code(symbol: Symbol) -> u8 {
+    let stress = match symbol.stress() {
+        Some(Stress::Primary) => 1,
+        Some(Stress::Secondary) => 2,
+        _ => 0,
+    };
+    (symbol.index() * 3 + stress) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let index = PronunciationIndex::new(&d);
+        assert_eq!(index.len(), d.iter().map(|(_, rules)| rules.len()).sum::<usize>());
+
+        let weights = PhoneticDistance::default();
+        let p = |word: &str| d.get(word).unwrap()[0].pronunciation();
+        for word in &["cat", "telephone", "strength"] {
+            let query = p(word);
+            let nearest = index.nearest(query, 5);
+            assert_eq!(nearest.len(), 5);
+            assert_eq!(nearest[0].distance(), 0.0);
+            assert!(nearest.iter().any(|n| &n.word() == word));
+
+            // the same as a linear scan over the whole dictionary
+            let mut all: Vec<f64> = d
+                .iter()
+                .flat_map(|(_, rules)| rules.iter())
+                .map(|r| weights.distance(query, r.pronunciation()))
+                .collect();
+            all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let found: Vec<f64> = nearest.iter().map(|n| n.distance()).collect();
+            for (a, b) in found.iter().zip(&all) {
+                assert!((a - b).abs() < 1e-9, "{}: {:?} vs {:?}", word, found, &all[..5]);
+            }
+        }
+
+        let within = index.within(p("cat"), 0.5);
+        assert!(within.iter().any(|n| n.word() == "pat"));
+        assert!(within.iter().all(|n| n.distance() <= 0.5));
+        assert!(index.nearest(p("cat"), 0).is_empty());
+    }
+}
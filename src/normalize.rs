@@ -0,0 +1,563 @@
+//! Reading numbers, dates, amounts of money and ordinals as words
+//!
+//! None of these have entries in the dictionary, so they are expanded into the words they are
+//! read as, which do. How a plain number is read depends on where it is: "1984" is a year in
+//! "in 1984" but a count in "1984 votes", and "5551234" is read digit by digit after "call".
+//! A `Hints` implementation decides which from the tokens either side of the number.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [&str; 5] = ["", "thousand", "million", "billion", "trillion"];
+
+const ORDINAL_ONES: [&str; 20] = [
+    "zeroth", "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth",
+    "ninth", "tenth", "eleventh", "twelfth", "thirteenth", "fourteenth", "fifteenth",
+    "sixteenth", "seventeenth", "eighteenth", "nineteenth",
+];
+const ORDINAL_TENS: [&str; 10] = [
+    "", "", "twentieth", "thirtieth", "fortieth", "fiftieth", "sixtieth", "seventieth",
+    "eightieth", "ninetieth",
+];
+const ORDINAL_SCALES: [&str; 5] = ["", "thousandth", "millionth", "billionth", "trillionth"];
+
+const PLURAL_TENS: [&str; 10] = [
+    "", "tens", "twenties", "thirties", "forties", "fifties", "sixties", "seventies",
+    "eighties", "nineties",
+];
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december",
+];
+
+// the most days each month can have
+const MONTH_DAYS: [u64; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+// (symbol, one unit, many units, one hundredth, many hundredths)
+const CURRENCIES: &[(char, &str, &str, &str, &str)] = &[
+    ('$', "dollar", "dollars", "cent", "cents"),
+    ('\u{a3}', "pound", "pounds", "penny", "pence"),
+    ('\u{20ac}', "euro", "euros", "cent", "cents"),
+];
+
+// the longest plain number that is read as a count rather than digit by digit
+const MAX_DIGITS: usize = 3 * SCALES.len();
+
+/// How a plain number should be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Hint {
+    /// As a count, like "one thousand nine hundred eighty four"
+    Cardinal,
+    /// As a position, like "twenty first"
+    Ordinal,
+    /// As a year, like "nineteen eighty four"
+    Year,
+    /// Digit by digit, like a telephone number
+    Digits,
+}
+
+/// Something that decides how a number should be read from the tokens around it
+///
+/// Closures taking the number and the tokens before and after it can be used as hints.
+pub trait Hints {
+    /// Decides how `token` should be read, or returns `None` to read it by its form alone
+    fn hint(&self, token: &str, previous: Option<&str>, next: Option<&str>) -> Option<Hint>;
+}
+
+impl<F> Hints for F
+where
+    F: Fn(&str, Option<&str>, Option<&str>) -> Option<Hint>,
+{
+    fn hint(&self, token: &str, previous: Option<&str>, next: Option<&str>) -> Option<Hint> {
+        self(token, previous, next)
+    }
+}
+
+/// Hints for English text
+///
+/// A day of the month after a month's name is an ordinal, a number after "in", "since",
+/// "until" and the like is a year, and a number after "call", "dial", "phone" or "number" is
+/// read digit by digit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnglishHints;
+
+impl Hints for EnglishHints {
+    fn hint(&self, token: &str, previous: Option<&str>, next: Option<&str>) -> Option<Hint> {
+        if !token.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value: u64 = token.parse().ok()?;
+        let previous = previous.map(str::to_lowercase);
+        let previous = previous.as_deref().map(|p| p.trim_end_matches('.'));
+        let next = next.map(str::to_lowercase);
+        let month = previous.is_some_and(|p| {
+            MONTHS.iter().any(|m| *m == p || (p.len() >= 3 && m.starts_with(p)))
+        });
+        match previous {
+            _ if month && (1..=31).contains(&value) => Some(Hint::Ordinal),
+            Some("the") if next.as_deref() == Some("of") && (1..=31).contains(&value) => {
+                Some(Hint::Ordinal)
+            }
+            _ if month && token.len() == 4 => Some(Hint::Year),
+            Some("in" | "since" | "from" | "until" | "till" | "by" | "during" | "circa"
+            | "year") if token.len() == 4 => Some(Hint::Year),
+            Some("call" | "dial" | "phone" | "number" | "tel" | "ext" | "extension" | "pin") => {
+                Some(Hint::Digits)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Expands a number, date, amount of money or ordinal into the words it is read as
+///
+/// `hint` only changes how plain numbers like "1984" are read. Without one, a four digit
+/// number from 1100 to 2099 is read as a year, a number with a leading zero is read digit by
+/// digit, and anything else is read as a count. Returns `None` if the token isn't any of
+/// these.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{verbalize, Cmudict, Hint};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let read = |token: &str, hint: Option<Hint>| verbalize(token, hint).map(|w| w.join(" "));
+/// assert_eq!(read("1984", None).as_deref(), Some("nineteen eighty four"));
+/// assert_eq!(
+///     read("1984", Some(Hint::Cardinal)).as_deref(),
+///     Some("one thousand nine hundred eighty four")
+/// );
+/// assert_eq!(read("$3.50", None).as_deref(), Some("three dollars and fifty cents"));
+/// assert_eq!(read("21st", None).as_deref(), Some("twenty first"));
+/// assert_eq!(read("10/17/2026", None).as_deref(), Some("october seventeenth twenty twenty six"));
+/// assert_eq!(read("911", Some(Hint::Digits)).as_deref(), Some("nine one one"));
+///
+/// // every word can be looked up
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// for word in verbalize("$3.50", None).unwrap() {
+///     assert!(dict.get(word).is_some());
+/// }
+///
+/// #   Ok(())
+/// # }
+/// ```
+pub fn verbalize(token: &str, hint: Option<Hint>) -> Option<Vec<&'static str>> {
+    let token = token.to_lowercase();
+    plain(&token, hint)
+        .or_else(|| currency(&token))
+        .or_else(|| ordinal_token(&token))
+        .or_else(|| decade(&token))
+        .or_else(|| date(&token))
+        .or_else(|| telephone(&token))
+        .or_else(|| number(&token))
+}
+
+// a run of digits, possibly grouped in thousands with commas
+fn plain(token: &str, hint: Option<Hint>) -> Option<Vec<&'static str>> {
+    if !token.starts_with(|c: char| c.is_ascii_digit())
+        || !token.chars().all(|c| c.is_ascii_digit() || c == ',')
+    {
+        return None;
+    }
+    let grouped = token.contains(',');
+    // a list like "1,2,3" isn't a number
+    let mut groups = token.split(',');
+    let first = groups.next().map_or(0, str::len);
+    if grouped && (first > 3 || groups.any(|group| group.len() != 3)) {
+        return None;
+    }
+    let digits: String = token.chars().filter(|&c| c != ',').collect();
+    let value: Option<u64> = if digits.len() <= MAX_DIGITS {
+        digits.parse().ok()
+    } else {
+        None
+    };
+
+    let mut words = Vec::new();
+    match (hint, value) {
+        (Some(Hint::Digits), _) | (_, None) => digits_of(&digits, &mut words),
+        (Some(Hint::Ordinal), Some(n)) => ordinal(n, &mut words),
+        (Some(Hint::Year), Some(n)) if n <= 9999 && !grouped => year(n, &mut words),
+        (Some(_), Some(n)) => cardinal(n, &mut words),
+        (None, Some(_)) if digits.len() > 1 && digits.starts_with('0') && !grouped => {
+            digits_of(&digits, &mut words)
+        }
+        (None, Some(n)) if (1100..=2099).contains(&n) && digits.len() == 4 && !grouped => {
+            year(n, &mut words)
+        }
+        (None, Some(n)) => cardinal(n, &mut words),
+    }
+    Some(words)
+}
+
+// an amount of money like "$3.50", "£1,000" or "$2.5m"
+fn currency(token: &str) -> Option<Vec<&'static str>> {
+    let symbol = token.chars().next()?;
+    let &(_, unit, units, cent, cents) = CURRENCIES.iter().find(|c| c.0 == symbol)?;
+    let amount = &token[symbol.len_utf8()..];
+    if !amount.contains(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let (amount, scale) = match amount.find(|c: char| c.is_alphabetic()) {
+        Some(idx) => {
+            let scale = match &amount[idx..] {
+                "k" | "thousand" => "thousand",
+                "m" | "mn" | "million" => "million",
+                "b" | "bn" | "billion" => "billion",
+                _ => return None,
+            };
+            (&amount[..idx], Some(scale))
+        }
+        None => (amount, None),
+    };
+    let (whole, fraction) = match amount.find('.') {
+        Some(idx) => (&amount[..idx], &amount[idx + 1..]),
+        None => (amount, ""),
+    };
+    let mut words = Vec::new();
+
+    // "$2.5m" and "$1.125" are read as decimals followed by the unit
+    if scale.is_some() || fraction.len() > 2 {
+        words.extend(number(amount)?);
+        words.extend(scale);
+        words.push(units);
+        return Some(words);
+    }
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        plain(whole, Some(Hint::Cardinal))?;
+        whole.replace(',', "").parse::<u64>().ok()?
+    };
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hundredths = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u64>().ok()? * 10,
+        _ => fraction.parse::<u64>().ok()?,
+    };
+    if whole > 0 || hundredths == 0 {
+        cardinal(whole, &mut words);
+        words.push(if whole == 1 { unit } else { units });
+    }
+    if hundredths > 0 {
+        if whole > 0 {
+            words.push("and");
+        }
+        cardinal(hundredths, &mut words);
+        words.push(if hundredths == 1 { cent } else { cents });
+    }
+    Some(words)
+}
+
+// a number with an ordinal suffix, like "21st"
+fn ordinal_token(token: &str) -> Option<Vec<&'static str>> {
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| token.strip_suffix(suffix))?;
+    plain(digits, Some(Hint::Ordinal))
+}
+
+// a decade, like "1980s" or "80's"
+fn decade(token: &str) -> Option<Vec<&'static str>> {
+    let digits = token.strip_suffix("'s").or_else(|| token.strip_suffix('s'))?;
+    if !digits.ends_with('0') || digits.contains(',') {
+        return None;
+    }
+    let mut words = plain(digits, None)?;
+    let last = words.last_mut()?;
+    *last = match TENS.iter().position(|t| t == last) {
+        Some(idx) => PLURAL_TENS[idx],
+        None if *last == "ten" => PLURAL_TENS[1],
+        None if *last == "hundred" => "hundreds",
+        None if *last == "thousand" => "thousands",
+        None => return None,
+    };
+    Some(words)
+}
+
+// a date like "10/17/2026", "17/10/2026", "2026-10-17", "10/17/26" or "17.10.2026", which
+// needs a year of two or four digits, and four after a dot
+fn date(token: &str) -> Option<Vec<&'static str>> {
+    let parts: Vec<&str> = token.split(['/', '-', '.']).collect();
+    let numeric = |p: &&str| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit());
+    if parts.len() != 3 || !parts.iter().all(numeric) {
+        return None;
+    }
+    let numbers: Vec<u64> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+    let separator = token.chars().find(|c| !c.is_ascii_digit())?;
+    let (month, day, y, year_digits) = match separator {
+        '-' if parts[0].len() == 4 => (numbers[1], numbers[2], numbers[0], 4),
+        '-' if parts[2].len() != 4 => return None,
+        '.' if parts[2].len() != 4 => return None,
+        '.' => (numbers[1], numbers[0], numbers[2], 4),
+        // the month comes first, unless it can't
+        _ if numbers[0] > 12 => (numbers[1], numbers[0], numbers[2], parts[2].len()),
+        _ => (numbers[0], numbers[1], numbers[2], parts[2].len()),
+    };
+    if !(1..=12).contains(&month) || !(1..=MONTH_DAYS[month as usize - 1]).contains(&day) {
+        return None;
+    }
+    let mut words = vec![MONTHS[month as usize - 1]];
+    ordinal(day, &mut words);
+    match year_digits {
+        4 => year(y, &mut words),
+        2 if y < 10 => {
+            words.push("oh");
+            words.push(ONES[y as usize]);
+        }
+        2 => cardinal(y, &mut words),
+        _ => return None,
+    }
+    Some(words)
+}
+
+// a telephone number like "555-1234" or "1-800-555-1234"
+fn telephone(token: &str) -> Option<Vec<&'static str>> {
+    let groups: Vec<&str> = token.split('-').collect();
+    let digits: String = groups.concat();
+    let valid = groups.len() >= 2
+        && groups.iter().all(|g| !g.is_empty() && g.chars().all(|c| c.is_ascii_digit()))
+        && digits.len() >= 7;
+    if !valid {
+        return None;
+    }
+    let mut words = Vec::new();
+    digits_of(&digits, &mut words);
+    Some(words)
+}
+
+// a number like "-1,234.5%"
+fn number(token: &str) -> Option<Vec<&'static str>> {
+    let (negative, rest) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+    let (rest, percent) = match rest.strip_suffix('%') {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    let (whole, fraction) = match rest.find('.') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    let mut words = Vec::new();
+    if negative {
+        words.push("minus");
+    }
+    if !whole.is_empty() {
+        words.extend(plain(whole, Some(Hint::Cardinal))?);
+    } else if fraction.is_none() {
+        return None;
+    }
+    if let Some(fraction) = fraction {
+        words.push("point");
+        digits_of(fraction, &mut words);
+    }
+    if percent {
+        words.push("percent");
+    }
+    Some(words)
+}
+
+fn digits_of(digits: &str, words: &mut Vec<&'static str>) {
+    for digit in digits.chars().filter_map(|c| c.to_digit(10)) {
+        words.push(ONES[digit as usize]);
+    }
+}
+
+fn cardinal(n: u64, words: &mut Vec<&'static str>) {
+    if n == 0 {
+        words.push(ONES[0]);
+        return;
+    }
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push((rest % 1000) as usize);
+        rest /= 1000;
+    }
+    for (scale, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        if group >= 100 {
+            words.push(ONES[group / 100]);
+            words.push("hundred");
+        }
+        let tens = group % 100;
+        if tens >= 20 {
+            words.push(TENS[tens / 10]);
+            if tens % 10 > 0 {
+                words.push(ONES[tens % 10]);
+            }
+        } else if tens > 0 {
+            words.push(ONES[tens]);
+        }
+        if scale > 0 {
+            words.push(SCALES[scale]);
+        }
+    }
+}
+
+fn ordinal(n: u64, words: &mut Vec<&'static str>) {
+    cardinal(n, words);
+    if let Some(last) = words.last_mut() {
+        *last = if let Some(idx) = ONES.iter().position(|w| w == last) {
+            ORDINAL_ONES[idx]
+        } else if let Some(idx) = TENS.iter().position(|w| w == last) {
+            ORDINAL_TENS[idx]
+        } else if let Some(idx) = SCALES.iter().position(|w| w == last) {
+            ORDINAL_SCALES[idx]
+        } else {
+            "hundredth"
+        };
+    }
+}
+
+// reads a year in pairs of digits, like "nineteen eighty four" or "nineteen oh five"
+fn year(n: u64, words: &mut Vec<&'static str>) {
+    let (high, low) = (n / 100, n % 100);
+    // "two thousand five", not "twenty oh five"
+    if n < 1000 || (high % 10 == 0 && low < 10) {
+        cardinal(n, words);
+        return;
+    }
+    cardinal(high, words);
+    match low {
+        0 => words.push("hundred"),
+        1..=9 => {
+            words.push("oh");
+            words.push(ONES[low as usize]);
+        }
+        _ => cardinal(low, words),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(token: &str, hint: Option<Hint>) -> Option<String> {
+        verbalize(token, hint).map(|words| words.join(" "))
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(read("0", None).as_deref(), Some("zero"));
+        assert_eq!(read("12", None).as_deref(), Some("twelve"));
+        assert_eq!(read("1,984", None).as_deref(), Some("one thousand nine hundred eighty four"));
+        let grouped = "twelve thousand three hundred forty five";
+        assert_eq!(read("12,345", None).as_deref(), Some(grouped));
+        assert_eq!(read("1,2,3", None), None);
+        assert_eq!(read("1234,567", None), None);
+        assert_eq!(read("1,", None), None);
+        assert_eq!(read("-3.25", None).as_deref(), Some("minus three point two five"));
+        assert_eq!(read("50%", None).as_deref(), Some("fifty percent"));
+        assert_eq!(read(".5", None).as_deref(), Some("point five"));
+        assert_eq!(read("2000000", None).as_deref(), Some("two million"));
+        assert_eq!(read("007", None).as_deref(), Some("zero zero seven"));
+        assert_eq!(read("12a", None), None);
+        assert_eq!(read("1.2.3.4", None), None);
+        assert_eq!(read("hello", None), None);
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(read("1984", None).as_deref(), Some("nineteen eighty four"));
+        assert_eq!(read("1905", None).as_deref(), Some("nineteen oh five"));
+        assert_eq!(read("1900", None).as_deref(), Some("nineteen hundred"));
+        assert_eq!(read("2005", None).as_deref(), Some("two thousand five"));
+        assert_eq!(read("2026", None).as_deref(), Some("twenty twenty six"));
+        assert_eq!(read("3000", None).as_deref(), Some("three thousand"));
+        assert_eq!(read("3000", Some(Hint::Year)).as_deref(), Some("three thousand"));
+        assert_eq!(read("1066", Some(Hint::Year)).as_deref(), Some("ten sixty six"));
+        assert_eq!(read("1980s", None).as_deref(), Some("nineteen eighties"));
+        assert_eq!(read("90's", None).as_deref(), Some("nineties"));
+    }
+
+    #[test]
+    fn ordinals_and_dates() {
+        assert_eq!(read("1st", None).as_deref(), Some("first"));
+        assert_eq!(read("21st", None).as_deref(), Some("twenty first"));
+        assert_eq!(read("112th", None).as_deref(), Some("one hundred twelfth"));
+        assert_eq!(read("100th", None).as_deref(), Some("one hundredth"));
+        assert_eq!(read("17", Some(Hint::Ordinal)).as_deref(), Some("seventeenth"));
+
+        let october = Some("october seventeenth twenty twenty six");
+        assert_eq!(read("10/17/2026", None).as_deref(), october);
+        assert_eq!(read("17/10/2026", None).as_deref(), october);
+        assert_eq!(read("2026-10-17", None).as_deref(), october);
+        assert_eq!(read("10-17-2026", None).as_deref(), october);
+        assert_eq!(read("17.10.2026", None).as_deref(), october);
+        assert_eq!(read("1/2/03", None).as_deref(), Some("january second oh three"));
+        assert_eq!(read("13/13/2026", None), None);
+        assert_eq!(read("2/31/2026", None), None);
+        assert_eq!(read("4/31/2026", None), None);
+        let leap = Some("february twenty ninth twenty twenty four");
+        assert_eq!(read("2/29/2024", None).as_deref(), leap);
+        // a version number isn't a date
+        assert_eq!(read("1.2.03", None), None);
+        assert_eq!(read("3.5.6", None), None);
+        assert_eq!(read("1/2/345", None), None);
+    }
+
+    #[test]
+    fn money_and_digits() {
+        assert_eq!(read("$3.50", None).as_deref(), Some("three dollars and fifty cents"));
+        assert_eq!(read("$1", None).as_deref(), Some("one dollar"));
+        assert_eq!(read("$0.01", None).as_deref(), Some("one cent"));
+        assert_eq!(read("$.5", None).as_deref(), Some("fifty cents"));
+        assert_eq!(read("\u{a3}1,000", None).as_deref(), Some("one thousand pounds"));
+        assert_eq!(read("$2.5m", None).as_deref(), Some("two point five million dollars"));
+        assert_eq!(read("$3x", None), None);
+        assert_eq!(read("$1,00", None), None);
+        assert_eq!(read("$", None), None);
+        assert_eq!(read("$.", None), None);
+        assert_eq!(read("$k", None), None);
+
+        assert_eq!(read("555-1234", None).as_deref(), Some("five five five one two three four"));
+        assert_eq!(read("5-10", None), None);
+        assert_eq!(read("911", Some(Hint::Digits)).as_deref(), Some("nine one one"));
+    }
+
+    #[test]
+    fn hints() {
+        let hints = EnglishHints;
+        assert_eq!(hints.hint("17", Some("October"), None), Some(Hint::Ordinal));
+        assert_eq!(hints.hint("17", Some("Oct."), None), Some(Hint::Ordinal));
+        assert_eq!(hints.hint("4", Some("the"), Some("of")), Some(Hint::Ordinal));
+        assert_eq!(hints.hint("1999", Some("in"), None), Some(Hint::Year));
+        assert_eq!(hints.hint("5551234", Some("call"), None), Some(Hint::Digits));
+        assert_eq!(hints.hint("17", Some("the"), Some("cats")), None);
+        assert_eq!(hints.hint("$5", Some("call"), None), None);
+
+        let always = |_: &str, _: Option<&str>, _: Option<&str>| Some(Hint::Digits);
+        assert_eq!(always.hint("12", None, None), Some(Hint::Digits));
+    }
+}
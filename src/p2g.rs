@@ -0,0 +1,410 @@
+//! Spelling suggestions for pronunciations
+//!
+//! The `Speller` first looks a pronunciation up in a reverse index of the dictionary, then
+//! makes up new spellings by choosing a grapheme for every phoneme. Which graphemes spell which
+//! phonemes is learnt by aligning every entry of the dictionary with an `Aligner`, and depends
+//! on the phonemes either side.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::align::Aligner;
+use crate::core::{Stress, Symbol};
+use crate::lookup::Source;
+use crate::Cmudict;
+
+// how many partial spellings are kept while generating
+const BEAM: usize = 64;
+// how many graphemes are tried for each phoneme
+const CHOICES: usize = 6;
+// contexts seen fewer times than this are ignored in favour of less specific ones
+const MIN_COUNT: u32 = 2;
+
+/// A possible spelling of a pronunciation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Spelling {
+    spelling: String,
+    source: Source,
+    score: f64,
+}
+
+impl Spelling {
+    /// Returns the suggested spelling
+    pub fn spelling(&self) -> &str {
+        &self.spelling
+    }
+
+    /// Returns `Source::Dictionary` for the spelling of a word in the dictionary, or
+    /// `Source::Predicted` for a made up spelling
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Returns the log probability of the spelling, which is 0 for dictionary words
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+/// Suggests spellings for pronunciations
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, Source, Speller, Symbol, Stress};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict: Cmudict = "
+/// cat K AE1 T
+/// hat HH AE1 T
+/// hit HH IH1 T
+/// kit K IH1 T
+/// ".trim().parse()?;
+/// let speller = Speller::train(&dict);
+///
+/// let kat = [Symbol::K, Symbol::AE(Stress::Primary), Symbol::T];
+/// let spellings = speller.spell(&kat, 5);
+/// assert_eq!(spellings[0].spelling(), "cat");
+/// assert_eq!(spellings[0].source(), Source::Dictionary);
+///
+/// let hik = [Symbol::HH, Symbol::IH(Stress::Primary), Symbol::K];
+/// assert_eq!(speller.spell(&hik, 5)[0].source(), Source::Predicted);
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Speller {
+    words: HashMap<Vec<Symbol>, Vec<String>>,
+    graphemes: HashMap<u64, Vec<(String, u32)>>,
+    letters: Letters,
+}
+
+impl Speller {
+    /// Trains a speller on every entry in a dictionary
+    pub fn train(dict: &Cmudict) -> Speller {
+        Speller::train_from(dict.iter().flat_map(|(label, rules)| {
+            rules.iter().map(move |rule| (label, rule.pronunciation()))
+        }))
+    }
+
+    /// Trains a speller on `(word, pronunciation)` pairs
+    pub fn train_from<'a, I>(entries: I) -> Speller
+    where
+        I: IntoIterator<Item = (&'a str, &'a [Symbol])>,
+    {
+        let entries: Vec<(&str, &[Symbol])> = entries.into_iter().collect();
+        let aligner = Aligner::train_from(entries.iter().copied());
+
+        let mut words: HashMap<Vec<Symbol>, Vec<String>> = HashMap::new();
+        let mut counts: HashMap<u64, HashMap<String, u32>> = HashMap::new();
+        let mut letters = Letters::default();
+        for &(word, phones) in &entries {
+            let spellings = words.entry(unstressed(phones)).or_default();
+            if !spellings.iter().any(|w| w == word) {
+                spellings.push(word.to_string());
+                letters.add(word);
+            }
+
+            let units = units(aligner.align_symbols(word, phones));
+            let phones: Vec<Symbol> = units.iter().flat_map(|(_, p)| p.iter().copied()).collect();
+            let mut pos = 0;
+            for (grapheme, chunk) in units {
+                for key in keys(&phones, pos, chunk.len()) {
+                    *counts.entry(key).or_default().entry(grapheme.clone()).or_insert(0) += 1;
+                }
+                pos += chunk.len();
+            }
+        }
+
+        let graphemes = counts
+            .into_iter()
+            .map(|(key, seen)| {
+                let mut seen: Vec<(String, u32)> = seen.into_iter().collect();
+                seen.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                (key, seen)
+            })
+            .collect();
+        Speller {
+            words,
+            graphemes,
+            letters,
+        }
+    }
+
+    /// Suggests up to `limit` spellings for a pronunciation, best first
+    ///
+    /// Stress is ignored. The spellings of dictionary words with the same pronunciation come
+    /// first, followed by made up spellings in order of how likely they are. Made up spellings
+    /// are scored both on how well their graphemes fit the phonemes and on how much their
+    /// letters look like an English word. An empty pronunciation has no spellings.
+    pub fn spell(&self, phones: &[Symbol], limit: usize) -> Vec<Spelling> {
+        if phones.is_empty() {
+            return Vec::new();
+        }
+        let phones = unstressed(phones);
+        let mut spellings: Vec<Spelling> = self
+            .words
+            .get(&phones)
+            .into_iter()
+            .flatten()
+            .map(|word| Spelling {
+                spelling: word.clone(),
+                source: Source::Dictionary,
+                score: 0.0,
+            })
+            .collect();
+
+        for (spelling, score) in self.generate(&phones) {
+            if spellings.len() >= limit {
+                break;
+            }
+            if !spellings.iter().any(|s| s.spelling == spelling) {
+                spellings.push(Spelling {
+                    spelling,
+                    source: Source::Predicted,
+                    score,
+                });
+            }
+        }
+        spellings.truncate(limit);
+        spellings
+    }
+
+    // every spelling the model can come up with, best first
+    fn generate(&self, phones: &[Symbol]) -> Vec<(String, f64)> {
+        // partial spellings ending at each position
+        let mut partial: Vec<Vec<(String, f64)>> = vec![Vec::new(); phones.len() + 1];
+        partial[0].push((String::new(), 0.0));
+        for pos in 0..phones.len() {
+            let mut here = best(std::mem::take(&mut partial[pos]));
+            here.truncate(BEAM);
+            for len in 1..=2 {
+                if pos + len > phones.len() {
+                    break;
+                }
+                let choices = match self.choices(phones, pos, len) {
+                    Some(choices) => choices,
+                    None => continue,
+                };
+                let total: u32 = choices.iter().map(|(_, c)| c).sum();
+                for (grapheme, count) in choices.iter().take(CHOICES) {
+                    let p = (*count as f64 / total as f64).ln();
+                    for (spelling, score) in &here {
+                        let extended = format!("{}{}", spelling, grapheme);
+                        let fit = self.letters.score(&extended, spelling.chars().count());
+                        partial[pos + len].push((extended, score + p + fit));
+                    }
+                }
+            }
+        }
+        let mut done = std::mem::take(&mut partial[phones.len()]);
+        for (spelling, score) in done.iter_mut() {
+            *score += self.letters.end(spelling);
+        }
+        best(done)
+    }
+
+    // the graphemes for the `len` phonemes at `pos`, from the most specific context that has
+    // been seen often enough
+    fn choices(&self, phones: &[Symbol], pos: usize, len: usize) -> Option<&[(String, u32)]> {
+        let mut fallback = None;
+        for key in keys(phones, pos, len) {
+            if let Some(choices) = self.graphemes.get(&key) {
+                let total: u32 = choices.iter().map(|(_, c)| c).sum();
+                if total >= MIN_COUNT {
+                    return Some(choices);
+                }
+                fallback = fallback.or(Some(&choices[..]));
+            }
+        }
+        fallback
+    }
+}
+
+// a letter trigram model of how words are spelled
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Letters {
+    trigrams: HashMap<u32, u32>,
+    bigrams: HashMap<u32, u32>,
+    unigrams: HashMap<u32, u32>,
+    // how often each pair and single letter is followed by something
+    histories: HashMap<u32, u32>,
+    total: u32,
+}
+
+// letter 0 marks the ends of a word
+const LETTERS: u32 = 32;
+
+impl Letters {
+    fn add(&mut self, word: &str) {
+        let codes = letter_codes(word);
+        for i in 2..codes.len() {
+            let (a, b, c) = (codes[i - 2], codes[i - 1], codes[i]);
+            *self.trigrams.entry((a * LETTERS + b) * LETTERS + c).or_insert(0) += 1;
+            *self.bigrams.entry(b * LETTERS + c).or_insert(0) += 1;
+            *self.unigrams.entry(c).or_insert(0) += 1;
+            *self.histories.entry((LETTERS + a) * LETTERS + b).or_insert(0) += 1;
+            *self.histories.entry(b).or_insert(0) += 1;
+            self.total += 1;
+        }
+    }
+
+    fn probability(&self, a: u32, b: u32, c: u32) -> f64 {
+        let ratio = |count: Option<&u32>, total: Option<&u32>| match (count, total) {
+            (Some(&count), Some(&total)) => count as f64 / total as f64,
+            _ => 0.0,
+        };
+        let trigram = ratio(
+            self.trigrams.get(&((a * LETTERS + b) * LETTERS + c)),
+            self.histories.get(&((LETTERS + a) * LETTERS + b)),
+        );
+        let bigram = ratio(self.bigrams.get(&(b * LETTERS + c)), self.histories.get(&b));
+        let unigram = ratio(self.unigrams.get(&c), Some(&self.total));
+        0.6 * trigram + 0.3 * bigram + 0.09 * unigram + 0.01 / LETTERS as f64
+    }
+
+    // the log probability of the letters of `spelling` after the first `from`
+    fn score(&self, spelling: &str, from: usize) -> f64 {
+        let codes = letter_codes(spelling);
+        // the last code marks the end of the word, which isn't scored yet
+        (from + 2..codes.len() - 1)
+            .map(|i| self.probability(codes[i - 2], codes[i - 1], codes[i]).ln())
+            .sum()
+    }
+
+    // the log probability of `spelling` ending where it does
+    fn end(&self, spelling: &str) -> f64 {
+        let codes = letter_codes(spelling);
+        let n = codes.len();
+        self.probability(codes[n - 3], codes[n - 2], codes[n - 1]).ln()
+    }
+}
+
+// the letters of a word with two markers before it and one after
+fn letter_codes(word: &str) -> Vec<u32> {
+    let mut codes = vec![0, 0];
+    codes.extend(word.chars().map(|c| match c {
+        'a'..='z' => c as u32 - 'a' as u32 + 1,
+        '\'' => 27,
+        '-' => 28,
+        '.' => 29,
+        _ => 30,
+    }));
+    codes.push(0);
+    codes
+}
+
+// keeps the best score of each spelling, however it was reached, best first
+fn best(mut spellings: Vec<(String, f64)>) -> Vec<(String, f64)> {
+    spellings.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.partial_cmp(&a.1).unwrap()));
+    spellings.dedup_by(|a, b| a.0 == b.0);
+    spellings.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.cmp(&b.0)));
+    spellings
+}
+
+fn unstressed(phones: &[Symbol]) -> Vec<Symbol> {
+    phones.iter().map(|s| s.with_stress(Stress::None)).collect()
+}
+
+// turns an alignment into graphemes that each make at least one sound, by attaching silent
+// letters to the grapheme before them (or after them at the start of a word)
+fn units(alignment: Vec<(String, Vec<Symbol>)>) -> Vec<(String, Vec<Symbol>)> {
+    let mut units: Vec<(String, Vec<Symbol>)> = Vec::new();
+    let mut silent = String::new();
+    for (grapheme, phones) in alignment {
+        if phones.is_empty() {
+            match units.last_mut() {
+                Some(last) => last.0.push_str(&grapheme),
+                None => silent.push_str(&grapheme),
+            }
+        } else {
+            let grapheme = std::mem::take(&mut silent) + &grapheme;
+            units.push((grapheme, unstressed(&phones)));
+        }
+    }
+    units
+}
+
+// keys for the phonemes at `pos`, from most to least specific context
+fn keys(phones: &[Symbol], pos: usize, len: usize) -> [u64; 4] {
+    let code = |idx: Option<usize>| match idx.and_then(|i| phones.get(i)) {
+        Some(s) => s.index() as u64 + 1,
+        None => 0,
+    };
+    let mut chunk = 0;
+    for phone in &phones[pos..pos + len] {
+        chunk = (chunk << 6) | (phone.index() as u64 + 1);
+    }
+    let left = code(pos.checked_sub(1));
+    let right = code(Some(pos + len));
+    // 12 bits of phonemes, 6 bits either side, and 2 bits saying which context it is
+    let key = |level: u64, left: u64, right: u64| (((level << 6 | left) << 12 | chunk) << 6) | right;
+    [
+        key(3, left, right),
+        key(2, left, 0),
+        key(1, 0, right),
+        key(0, 0, 0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spelling() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let speller = Speller::train_from(crate::sample(&d, &["ba", "be", "bl"]));
+
+        let bear = d.get("bear").unwrap()[0].pronunciation();
+        let spellings = speller.spell(bear, 10);
+        let known: Vec<&str> = spellings
+            .iter()
+            .filter(|s| s.source() == Source::Dictionary)
+            .map(|s| s.spelling())
+            .collect();
+        assert!(known.contains(&"bear") && known.contains(&"bare"));
+        assert!(spellings.len() > known.len());
+
+        use Symbol::*;
+        let blamp = [B, L, AE(Stress::Primary), M, P, S];
+        let spellings = speller.spell(&blamp, 5);
+        assert_eq!(spellings.len(), 5);
+        assert!(spellings.iter().all(|s| s.source() == Source::Predicted));
+        assert!(spellings.iter().any(|s| s.spelling() == "blamps"));
+        assert!(speller.spell(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn silent_letters() {
+        use Symbol::*;
+        let alignment = vec![
+            ("m".to_string(), vec![M]),
+            ("a".to_string(), vec![EY(Stress::Primary)]),
+            ("k".to_string(), vec![K]),
+            ("e".to_string(), vec![]),
+        ];
+        assert_eq!(
+            units(alignment),
+            vec![
+                ("m".to_string(), vec![M]),
+                ("a".to_string(), vec![EY(Stress::None)]),
+                ("ke".to_string(), vec![K]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Finding minimal pairs, words whose pronunciations differ by a single sound
+
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::alphabet::ALL;
+use crate::core::{Rule, Stress, Symbol};
+use crate::Cmudict;
+
+/// Where in a word the sounds of a minimal pair differ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Position {
+    /// The first sound of the word
+    Initial,
+    /// Neither the first nor the last sound
+    Medial,
+    /// The last sound of the word
+    Final,
+}
+
+/// Which minimal pairs to find, by where their sounds differ and how many syllables their
+/// first word has
+///
+/// The default filter keeps every pair.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairFilter {
+    position: Option<Position>,
+    syllables: Option<usize>,
+}
+
+impl PairFilter {
+    /// Keeps only the pairs that differ at a position
+    pub fn with_position(mut self, position: Position) -> PairFilter {
+        self.position = Some(position);
+        self
+    }
+
+    /// Keeps only the pairs whose first word has a number of syllables
+    pub fn with_syllables(mut self, syllables: usize) -> PairFilter {
+        self.syllables = Some(syllables);
+        self
+    }
+
+    // whether the pairs of `phones` that differ at `idx` are kept
+    fn accepts(&self, phones: &[Symbol], idx: usize) -> bool {
+        let position = match self.position {
+            Some(position) => position == position_of(idx, phones.len()),
+            None => true,
+        };
+        let syllables = match self.syllables {
+            Some(syllables) => syllables == count_syllables(phones),
+            None => true,
+        };
+        position && syllables
+    }
+}
+
+/// Two words whose pronunciations are the same apart from one sound
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimalPair<'a> {
+    first: (&'a str, &'a Rule),
+    second: (&'a str, &'a Rule),
+    index: usize,
+}
+
+impl<'a> MinimalPair<'a> {
+    /// Returns the first word and its pronunciation
+    pub fn first(&self) -> (&'a str, &'a Rule) {
+        self.first
+    }
+
+    /// Returns the second word and its pronunciation
+    pub fn second(&self) -> (&'a str, &'a Rule) {
+        self.second
+    }
+
+    /// Returns the index of the symbol that differs
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the symbols that differ, from the first word and then the second
+    pub fn contrast(&self) -> (Symbol, Symbol) {
+        (
+            self.first.1.pronunciation()[self.index],
+            self.second.1.pronunciation()[self.index],
+        )
+    }
+
+    /// Returns where in the words the symbols differ
+    pub fn position(&self) -> Position {
+        position_of(self.index, self.first.1.pronunciation().len())
+    }
+
+    /// Returns the number of syllables in the first word
+    ///
+    /// The second word has the same number unless a vowel is paired with a consonant.
+    pub fn syllables(&self) -> usize {
+        count_syllables(self.first.1.pronunciation())
+    }
+}
+
+impl Cmudict {
+    /// Finds the minimal pairs for a contrast between two sounds
+    ///
+    /// The first word of each pair has `a` where the second has `b`, and their pronunciations
+    /// are otherwise exactly the same. The stress of `a` and `b` is ignored, but when they are
+    /// both vowels they need the same stress in the two words, while a vowel in place of a
+    /// consonant may have any stress. Only the pairs the filter keeps are found, ordered by
+    /// their words.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, PairFilter, Position, Symbol};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let filter = PairFilter::default().with_position(Position::Initial).with_syllables(1);
+    /// let initial: Vec<(&str, &str)> = dict
+    ///     .minimal_pairs(Symbol::R, Symbol::L, filter)
+    ///     .iter()
+    ///     .map(|pair| (pair.first().0, pair.second().0))
+    ///     .collect();
+    /// assert!(initial.contains(&("rock", "lock")));
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn minimal_pairs(&self, a: Symbol, b: Symbol, filter: PairFilter) -> Vec<MinimalPair<'_>> {
+        let (a, b) = (a.with_stress(Stress::None), b.with_stress(Stress::None));
+        if a == b {
+            return Vec::new();
+        }
+        let index = self.by_pronunciation();
+        let mut pairs = Vec::new();
+        for (word, rules) in self.iter() {
+            for rule in rules {
+                let phones = rule.pronunciation();
+                for (idx, &symbol) in phones.iter().enumerate() {
+                    if symbol.with_stress(Stress::None) != a || !filter.accepts(phones, idx) {
+                        continue;
+                    }
+                    for &stress in stresses(symbol, b) {
+                        let mut other = phones.to_vec();
+                        other[idx] = b.with_stress(stress);
+                        pairs.extend(matching(&index, word, rule, &other, idx));
+                    }
+                }
+            }
+        }
+        sort(&mut pairs);
+        pairs
+    }
+
+    /// Finds every minimal pair with a word, whatever the contrast
+    ///
+    /// The word is always first in the pair. Every pronunciation of the word is used, and
+    /// only the pairs the filter keeps are found.
+    pub fn minimal_pairs_of(&self, word: &str, filter: PairFilter) -> Vec<MinimalPair<'_>> {
+        let (word, rules) = match self.map.get_key_value(word) {
+            Some((word, rules)) => (&word[..], &rules[..]),
+            None => return Vec::new(),
+        };
+        let index = self.by_pronunciation();
+        let mut pairs = Vec::new();
+        for rule in rules {
+            let phones = rule.pronunciation();
+            for (idx, &symbol) in phones.iter().enumerate() {
+                if !filter.accepts(phones, idx) {
+                    continue;
+                }
+                for &replacement in ALL.iter() {
+                    if replacement == symbol.with_stress(Stress::None) {
+                        continue;
+                    }
+                    for &stress in stresses(symbol, replacement) {
+                        let mut other = phones.to_vec();
+                        other[idx] = replacement.with_stress(stress);
+                        pairs.extend(matching(&index, word, rule, &other, idx));
+                    }
+                }
+            }
+        }
+        sort(&mut pairs);
+        pairs
+    }
+
+    // every pronunciation and the words that have it
+    fn by_pronunciation(&self) -> HashMap<&[Symbol], Vec<(&str, &Rule)>> {
+        let mut index: HashMap<&[Symbol], Vec<(&str, &Rule)>> = HashMap::new();
+        for (word, rules) in self.iter() {
+            for rule in rules {
+                index.entry(rule.pronunciation()).or_default().push((word, rule));
+            }
+        }
+        index
+    }
+}
+
+// the pairs of a word with every other word pronounced `other`
+fn matching<'a>(
+    index: &HashMap<&[Symbol], Vec<(&'a str, &'a Rule)>>,
+    word: &'a str,
+    rule: &'a Rule,
+    other: &[Symbol],
+    idx: usize,
+) -> Vec<MinimalPair<'a>> {
+    index
+        .get(other)
+        .map(|found| {
+            found
+                .iter()
+                .filter(|&&(second, _)| second != word)
+                .map(|&second| MinimalPair {
+                    first: (word, rule),
+                    second,
+                    index: idx,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// the stresses a symbol can have in place of another: a vowel keeps the stress of the vowel it
+// replaces, or may have any stress in place of a consonant
+fn stresses(symbol: Symbol, replacement: Symbol) -> &'static [Stress] {
+    match symbol.stress() {
+        _ if !replacement.is_syllable() => &[Stress::None],
+        Some(Stress::Primary) => &[Stress::Primary],
+        Some(Stress::Secondary) => &[Stress::Secondary],
+        Some(Stress::None) => &[Stress::None],
+        None => &[Stress::None, Stress::Primary, Stress::Secondary],
+    }
+}
+
+fn position_of(idx: usize, len: usize) -> Position {
+    if idx == 0 {
+        Position::Initial
+    } else if idx + 1 == len {
+        Position::Final
+    } else {
+        Position::Medial
+    }
+}
+
+fn count_syllables(phones: &[Symbol]) -> usize {
+    phones.iter().filter(|s| s.is_syllable()).count()
+}
+
+fn sort(pairs: &mut [MinimalPair<'_>]) {
+    pairs.sort_by(|x, y| {
+        (x.first.0, x.second.0, x.first.1.label(), x.second.1.label(), x.index).cmp(&(
+            y.first.0,
+            y.second.0,
+            y.first.1.label(),
+            y.second.1.label(),
+            y.index,
+        ))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+
+    #[test]
+    fn contrasts() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let words = |pairs: &[MinimalPair]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|p| (p.first().0.to_string(), p.second().0.to_string()))
+                .collect()
+        };
+
+        let all = PairFilter::default();
+        let vowels = d.minimal_pairs(IH(Stress::None), IY(Stress::Primary), all);
+        let found = words(&vowels);
+        assert!(found.contains(&("bit".to_string(), "beat".to_string())));
+        assert!(found.contains(&("ship".to_string(), "sheep".to_string())));
+        assert!(!found.contains(&("beat".to_string(), "bit".to_string())));
+        let bit = vowels.iter().find(|p| p.first().0 == "bit").unwrap();
+        assert_eq!(bit.position(), Position::Medial);
+        assert_eq!(bit.contrast(), (IH(Stress::Primary), IY(Stress::Primary)));
+
+        let final_ = d.minimal_pairs(T, D, all);
+        let bat = final_.iter().find(|p| p.first().0 == "bat" && p.second().0 == "bad");
+        assert_eq!(bat.map(|p| p.position()), Some(Position::Final));
+        assert!(d.minimal_pairs(T, T, all).is_empty());
+
+        let filter = all.with_position(Position::Final).with_syllables(1);
+        let filtered = d.minimal_pairs(T, D, filter);
+        let expected: Vec<&MinimalPair> = final_
+            .iter()
+            .filter(|p| p.position() == Position::Final && p.syllables() == 1)
+            .collect();
+        assert_eq!(filtered.iter().collect::<Vec<_>>(), expected);
+        assert!(filtered.iter().any(|p| p.first().0 == "bat" && p.second().0 == "bad"));
+        assert!(!words(&filtered).contains(&("tip".to_string(), "dip".to_string())));
+    }
+
+    #[test]
+    fn pairs_of_a_word() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let cat = d.minimal_pairs_of("cat", PairFilter::default());
+        let found: Vec<&str> = cat.iter().map(|p| p.second().0).collect();
+        for word in &["bat", "cap", "cut", "kit", "can"] {
+            assert!(found.contains(word), "{} isn't paired with cat", word);
+        }
+        assert!(cat.iter().all(|p| p.first().0 == "cat" && p.syllables() == 1));
+        assert!(d.minimal_pairs_of("qwxyzzy", PairFilter::default()).is_empty());
+
+        let filter = PairFilter::default().with_position(Position::Initial);
+        let initial = d.minimal_pairs_of("cat", filter);
+        assert!(initial.iter().all(|p| p.position() == Position::Initial));
+        assert!(initial.iter().any(|p| p.second().0 == "bat"));
+        assert!(!initial.iter().any(|p| p.second().0 == "cap"));
+        let two = d.minimal_pairs_of("cat", PairFilter::default().with_syllables(2));
+        assert!(two.is_empty());
+
+        // a vowel in place of a consonant is found by both, whatever its stress
+        let tie = d.minimal_pairs_of("tie", PairFilter::default());
+        let vowels: Vec<&MinimalPair> =
+            tie.iter().filter(|p| p.contrast().0 == T && p.contrast().1.is_syllable()).collect();
+        assert!(vowels.iter().any(|p| p.second().0 == "ai"));
+        for pair in vowels {
+            let (a, b) = pair.contrast();
+            let pairs = d.minimal_pairs(a, b.with_stress(Stress::None), PairFilter::default());
+            assert!(pairs.contains(pair), "{:?} isn't found from its contrast", pair);
+        }
+    }
+}
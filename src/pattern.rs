@@ -0,0 +1,711 @@
+//! Regular expressions over pronunciations
+//!
+//! A pattern is a sequence of items separated by whitespace, much like a pronunciation in the
+//! dictionary:
+//!
+//! - `AE1` matches exactly that symbol, and `AE` matches it with any stress
+//! - `.` matches any symbol
+//! - `[vowel]` matches any symbol of a class, and `[P T K]` any of the symbols listed. Classes
+//!   and symbols can be mixed, and `[^nasal]` matches anything not in the brackets. The
+//!   classes are `vowel`, `consonant`, `stop`, `affricate`, `fricative`, `nasal`, `liquid`,
+//!   `glide`, `sonorant`, `obstruent`, `voiced`, `voiceless`, `stressed`, `primary`,
+//!   `secondary` and `unstressed`
+//! - `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}` repeat the item before them
+//! - `(` and `)` group items, and `|` separates alternatives
+//! - `^` and `$` match the start and end of the pronunciation
+//!
+//! Like other regular expressions a pattern can match anywhere in a pronunciation unless it is
+//! anchored. Matching runs every alternative at once rather than backtracking, so it takes
+//! time proportional to the length of the pronunciation times the size of the pattern.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{ParseError, ParseResult};
+use crate::Cmudict;
+
+// patterns that repeat so much they'd compile to more instructions than this are refused
+const MAX_PROGRAM: usize = 10_000;
+
+// patterns that nest groups and repetitions deeper than this are refused before they can use up
+// the stack
+const MAX_DEPTH: usize = 64;
+
+/// A compiled pattern over pronunciations
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Pattern, Symbol, Stress};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// // starts with a stop, has three syllables, and ends in NG
+/// let pattern: Pattern = "^ [stop] [consonant]* ([vowel] [consonant]*){2} [vowel] NG $".parse()?;
+/// let parking = [
+///     Symbol::P,
+///     Symbol::AA(Stress::Primary),
+///     Symbol::R,
+///     Symbol::K,
+///     Symbol::IH(Stress::None),
+///     Symbol::NG,
+/// ];
+/// assert!(!pattern.is_match(&parking));
+///
+/// let ending = "NG $".parse::<Pattern>()?;
+/// assert!(ending.is_match(&parking));
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    source: String,
+    program: Vec<Inst>,
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Symbol(Matcher),
+    Split(usize, usize),
+    Jump(usize),
+    Start,
+    End,
+    Accept,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Matcher {
+    Any,
+    Exact(Symbol),
+    // any stress
+    Base(Symbol),
+    Class(Class),
+    Set(Vec<Matcher>, bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Class {
+    Vowel,
+    Consonant,
+    Stop,
+    Affricate,
+    Fricative,
+    Nasal,
+    Liquid,
+    Glide,
+    Sonorant,
+    Obstruent,
+    Voiced,
+    Voiceless,
+    Stressed,
+    Primary,
+    Secondary,
+    Unstressed,
+}
+
+// an item of a sequence that can't repeat or have alternatives
+#[derive(Debug, Clone)]
+pub(crate) enum Item {
+    Symbol(Matcher),
+    Start,
+    End,
+    // `#`, between two words
+    Boundary,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Match(Matcher),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+impl Pattern {
+    /// Compiles a pattern
+    pub fn new(pattern: &str) -> ParseResult<Pattern> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let node = parser.alternation()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(ParseError::UnexpectedCharacter("an item of a pattern", c));
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Inst::Accept);
+        Ok(Pattern {
+            source: pattern.to_string(),
+            program,
+        })
+    }
+
+    /// Returns `true` if the pattern matches anywhere in the pronunciation
+    pub fn is_match(&self, phones: &[Symbol]) -> bool {
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        let mut seen = vec![usize::MAX; self.program.len()];
+        for pos in 0..=phones.len() {
+            // a new attempt starts at every position
+            if self.add(&mut current, &mut seen, 0, pos, phones.len()) {
+                return true;
+            }
+            let symbol = match phones.get(pos) {
+                Some(symbol) => symbol,
+                None => break,
+            };
+            for &pc in &current {
+                if let Inst::Symbol(ref matcher) = self.program[pc] {
+                    if matcher.matches(symbol)
+                        && self.add(&mut next, &mut seen, pc + 1, pos + 1, phones.len())
+                    {
+                        return true;
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        false
+    }
+
+    // follows every jump from `pc`, keeping the instructions that consume a symbol, and
+    // returns `true` if the pattern can accept
+    fn add(
+        &self,
+        threads: &mut Vec<usize>,
+        seen: &mut [usize],
+        pc: usize,
+        pos: usize,
+        len: usize,
+    ) -> bool {
+        if seen[pc] == pos {
+            return false;
+        }
+        seen[pc] = pos;
+        match self.program[pc] {
+            Inst::Symbol(_) => {
+                threads.push(pc);
+                false
+            }
+            Inst::Split(a, b) => {
+                self.add(threads, seen, a, pos, len) || self.add(threads, seen, b, pos, len)
+            }
+            Inst::Jump(to) => self.add(threads, seen, to, pos, len),
+            Inst::Start => pos == 0 && self.add(threads, seen, pc + 1, pos, len),
+            Inst::End => pos == len && self.add(threads, seen, pc + 1, pos, len),
+            Inst::Accept => true,
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<Pattern> {
+        Pattern::new(s)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Cmudict {
+    /// Finds every pronunciation that matches a pattern
+    ///
+    /// The words are returned with the pronunciation that matched, in alphabetical order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, Pattern};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let pattern: Pattern = "^ S T R [vowel] NG [consonant]* $".parse()?;
+    /// let words: Vec<&str> = dict.search(&pattern).iter().map(|(word, _)| *word).collect();
+    /// assert!(words.contains(&"strong"));
+    /// assert!(words.contains(&"strings"));
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn search(&self, pattern: &Pattern) -> Vec<(&str, &Rule)> {
+        let mut found: Vec<(&str, &Rule)> = self
+            .iter()
+            .flat_map(|(word, rules)| rules.iter().map(move |rule| (word, rule)))
+            .filter(|(_, rule)| pattern.is_match(rule.pronunciation()))
+            .collect();
+        found.sort_by(|a, b| (a.0, a.1.label()).cmp(&(b.0, b.1.label())));
+        found
+    }
+}
+
+impl Matcher {
+    pub(crate) fn matches(&self, symbol: &Symbol) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::Exact(s) => s == symbol,
+            Matcher::Base(s) => *s == symbol.with_stress(Stress::None),
+            Matcher::Class(class) => class.contains(symbol),
+            Matcher::Set(items, negated) => items.iter().any(|m| m.matches(symbol)) != *negated,
+        }
+    }
+}
+
+impl Class {
+    fn from_name(name: &str) -> Option<Class> {
+        let class = match name {
+            "vowel" => Class::Vowel,
+            "consonant" => Class::Consonant,
+            "stop" => Class::Stop,
+            "affricate" => Class::Affricate,
+            "fricative" => Class::Fricative,
+            "nasal" => Class::Nasal,
+            "liquid" => Class::Liquid,
+            "glide" => Class::Glide,
+            "sonorant" => Class::Sonorant,
+            "obstruent" => Class::Obstruent,
+            "voiced" => Class::Voiced,
+            "voiceless" => Class::Voiceless,
+            "stressed" => Class::Stressed,
+            "primary" => Class::Primary,
+            "secondary" => Class::Secondary,
+            "unstressed" => Class::Unstressed,
+            _ => return None,
+        };
+        Some(class)
+    }
+
+    fn contains(self, symbol: &Symbol) -> bool {
+        match self {
+            Class::Vowel => symbol.is_vowel(),
+            Class::Consonant => symbol.is_consonant(),
+            Class::Stop => symbol.is_stop(),
+            Class::Affricate => symbol.is_affricate(),
+            Class::Fricative => symbol.is_fricative(),
+            Class::Nasal => symbol.is_nasal(),
+            Class::Liquid => symbol.is_liquid(),
+            Class::Glide => symbol.is_glide(),
+            Class::Sonorant => symbol.is_sonorant(),
+            Class::Obstruent => !symbol.is_sonorant(),
+            Class::Voiced => symbol.is_voiced(),
+            Class::Voiceless => !symbol.is_voiced(),
+            Class::Stressed => symbol.is_primary() || symbol.is_secondary(),
+            Class::Primary => symbol.is_primary(),
+            Class::Secondary => symbol.is_secondary(),
+            Class::Unstressed => symbol.is_unstressed(),
+        }
+    }
+}
+
+// parses a sequence of symbols, classes, sets, `^`, `$` and `#`, like the contexts of rewrite
+// rules
+pub(crate) fn sequence(s: &str) -> ParseResult<Vec<Item>> {
+    let mut parser = Parser {
+        chars: s.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let mut items = Vec::new();
+    loop {
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => return Ok(items),
+            Some('#') => {
+                parser.pos += 1;
+                items.push(Item::Boundary);
+                continue;
+            }
+            Some(_) => {}
+        }
+        let item = match parser.item()? {
+            Node::Match(matcher) => Item::Symbol(matcher),
+            Node::Start => Item::Start,
+            Node::End => Item::End,
+            _ => return Err(ParseError::InvalidPattern(format!("{} has a group in it", s))),
+        };
+        if let Some(c) = parser.peek().filter(|c| "*+?{".contains(*c)) {
+            return Err(ParseError::UnexpectedCharacter("an item of a sequence", c));
+        }
+        items.push(item);
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    // how many groups and repetitions the current item is inside
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn alternation(&mut self) -> ParseResult<Node> {
+        let mut alternatives = vec![self.concatenation()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.concatenation()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Node::Alternate(alternatives),
+        })
+    }
+
+    fn concatenation(&mut self) -> ParseResult<Node> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                Some(_) => items.push(self.repetition()?),
+            }
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn repetition(&mut self) -> ParseResult<Node> {
+        let mut node = self.item()?;
+        let depth = self.depth;
+        loop {
+            let (min, max) = match self.peek() {
+                Some('{') => self.braces()?,
+                Some(c) => {
+                    let bounds = match c {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        '?' => (0, Some(1)),
+                        _ => break,
+                    };
+                    self.pos += 1;
+                    bounds
+                }
+                None => break,
+            };
+            node = Node::Repeat(Box::new(node), min, max);
+            self.descend()?;
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    // `{n}`, `{n,}` or `{n,m}`
+    fn braces(&mut self) -> ParseResult<(usize, Option<usize>)> {
+        self.expect('{', "an opening brace")?;
+        let min = self.number()?;
+        let max = match self.peek() {
+            Some(',') => {
+                self.pos += 1;
+                match self.peek() {
+                    Some('}') => None,
+                    _ => Some(self.number()?),
+                }
+            }
+            _ => Some(min),
+        };
+        self.expect('}', "a closing brace")?;
+        if max.is_some_and(|max| max < min) {
+            return Err(ParseError::InvalidPattern(format!(
+                "{{{},{}}} repeats at least more than at most",
+                min,
+                max.unwrap_or_default()
+            )));
+        }
+        Ok((min, max))
+    }
+
+    fn item(&mut self) -> ParseResult<Node> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                self.descend()?;
+                let node = self.alternation()?;
+                self.depth -= 1;
+                self.skip_whitespace();
+                self.expect(')', "a closing parenthesis")?;
+                Ok(node)
+            }
+            Some('[') => {
+                self.pos += 1;
+                let negated = self.peek() == Some('^');
+                if negated {
+                    self.pos += 1;
+                }
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(']') => break,
+                        Some(',') => self.pos += 1,
+                        Some(_) => {
+                            let word = self.word()?;
+                            match Class::from_name(&word.to_lowercase()) {
+                                Some(class) => items.push(Matcher::Class(class)),
+                                None => items.push(literal(&word)?),
+                            }
+                        }
+                        None => return Err(ParseError::UnexpectedEOF("a closing bracket")),
+                    }
+                }
+                self.pos += 1;
+                Ok(Node::Match(match (items.len(), negated) {
+                    (1, false) => items.remove(0),
+                    _ => Matcher::Set(items, negated),
+                }))
+            }
+            Some('.') => {
+                self.pos += 1;
+                Ok(Node::Match(Matcher::Any))
+            }
+            Some('^') => {
+                self.pos += 1;
+                Ok(Node::Start)
+            }
+            Some('$') => {
+                self.pos += 1;
+                Ok(Node::End)
+            }
+            _ => {
+                let word = self.word()?;
+                Ok(Node::Match(literal(&word)?))
+            }
+        }
+    }
+
+    fn descend(&mut self) -> ParseResult<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError::InvalidPattern("it nests too deeply".to_string()));
+        }
+        Ok(())
+    }
+
+    // a symbol or class name
+    fn word(&mut self) -> ParseResult<String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        match self.peek() {
+            _ if self.pos > start => Ok(self.chars[start..self.pos].iter().collect()),
+            Some(c) => Err(ParseError::UnexpectedCharacter("a symbol", c)),
+            None => Err(ParseError::UnexpectedEOF("a symbol")),
+        }
+    }
+
+    fn number(&mut self) -> ParseResult<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        match self.peek() {
+            _ if !digits.is_empty() => digits
+                .parse()
+                .map_err(|_| ParseError::InvalidPattern(format!("{} repeats too often", digits))),
+            Some(c) => Err(ParseError::UnexpectedCharacter("a number", c)),
+            None => Err(ParseError::UnexpectedEOF("a number")),
+        }
+    }
+
+    fn expect(&mut self, c: char, what: &'static str) -> ParseResult<()> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(found) => Err(ParseError::UnexpectedCharacter(what, found)),
+            None => Err(ParseError::UnexpectedEOF(what)),
+        }
+    }
+}
+
+// a symbol, with its stress only mattering if it has one
+fn literal(word: &str) -> ParseResult<Matcher> {
+    let upper = word.to_uppercase();
+    let unknown = || ParseError::InvalidPattern(format!("{} isn't a symbol or class", word));
+    let symbol = Symbol::from_str(&upper).map_err(|_| unknown())?;
+    let stressed = upper.ends_with(|c: char| c.is_ascii_digit());
+    // the symbol parser stops once it has a symbol, so check nothing was left over
+    let written = symbol.to_string();
+    if written != upper && (stressed || written[..written.len() - 1] != upper) {
+        return Err(unknown());
+    }
+    if symbol.is_syllable() && !stressed {
+        Ok(Matcher::Base(symbol))
+    } else {
+        Ok(Matcher::Exact(symbol))
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> ParseResult<()> {
+    if program.len() > MAX_PROGRAM {
+        return Err(ParseError::InvalidPattern("it repeats too much".to_string()));
+    }
+    match node {
+        Node::Match(matcher) => program.push(Inst::Symbol(matcher.clone())),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(items) => {
+            for item in items {
+                compile(item, program)?;
+            }
+        }
+        Node::Alternate(alternatives) => {
+            let mut jumps = Vec::new();
+            for (idx, alternative) in alternatives.iter().enumerate() {
+                if idx + 1 < alternatives.len() {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(alternative, program)?;
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    let next = program.len();
+                    program[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile(alternative, program)?;
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat(item, min, max) => {
+            for _ in 0..*min {
+                compile(item, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(item, program)?;
+                    program.push(Inst::Jump(split));
+                    let end = program.len();
+                    program[split] = Inst::Split(split + 1, end);
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(item, program)?;
+                        if program.len() > MAX_PROGRAM {
+                            break;
+                        }
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+
+    fn matches(pattern: &str, phones: &[Symbol]) -> bool {
+        Pattern::new(pattern).unwrap().is_match(phones)
+    }
+
+    #[test]
+    fn matching() {
+        let cat = [K, AE(Stress::Primary), T];
+        assert!(matches("AE", &cat));
+        assert!(matches("AE1", &cat));
+        assert!(!matches("AE0", &cat));
+        assert!(matches("^ K", &cat));
+        assert!(!matches("^ AE", &cat));
+        assert!(matches("[stop] $", &cat));
+        assert!(matches("^ [stop] [vowel] [stop] $", &cat));
+        assert!(matches("^ . . . $", &cat));
+        assert!(!matches("^ . . $", &cat));
+        assert!(matches("^ [P T K] [^consonant] [voiceless]", &cat));
+        assert!(!matches("[nasal]", &cat));
+        assert!(matches("^ (B | K) AE (T | D) $", &cat));
+        assert!(matches("^ [consonant]* [primary] .+ $", &cat));
+        assert!(matches("^ .{3} $", &cat));
+        assert!(matches("^ .{2,} $", &cat));
+        assert!(!matches("^ .{4,5} $", &cat));
+        assert!(matches("^ K? AE T $", &[AE(Stress::None), T]));
+        assert!(matches("^ $", &[]));
+        assert!(matches("", &cat));
+        // empty loops don't hang
+        assert!(matches("^ (K*)* AE", &cat));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Pattern::new("[vowel").is_err());
+        assert!(Pattern::new("(K AE").is_err());
+        assert!(Pattern::new("K AE)").is_err());
+        assert!(Pattern::new("[bogus]").is_err());
+        assert!(Pattern::new("XX").is_err());
+        assert!(Pattern::new("K{3,1}").is_err());
+        assert!(Pattern::new("K{100000}").is_err());
+        let nested = |depth: usize| format!("{}AH{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Pattern::new(&nested(MAX_DEPTH)).is_ok());
+        assert!(Pattern::new(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(Pattern::new(&nested(3000)).is_err());
+        assert!(Pattern::new(&format!("AH{}", "?".repeat(100_000))).is_err());
+        assert_eq!(Pattern::new("^ K .*").unwrap().to_string(), "^ K .*");
+    }
+
+    #[test]
+    fn searching() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let pattern = Pattern::new("^ [stop] .* NG $").unwrap();
+        let found = d.search(&pattern);
+        assert!(found.iter().any(|(word, _)| *word == "parking"));
+        assert!(found.iter().all(|(_, rule)| rule.pronunciation().last() == Some(&NG)));
+        let pattern = "^ [stop] [consonant]* ([vowel] [consonant]*){2} [vowel] NG $";
+        let syllables: Vec<&str> = d
+            .search(&Pattern::new(pattern).unwrap())
+            .iter()
+            .map(|(word, _)| *word)
+            .collect();
+        assert!(syllables.contains(&"computing"));
+        assert!(!syllables.contains(&"parking"));
+    }
+}
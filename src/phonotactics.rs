@@ -0,0 +1,342 @@
+//! Checking that pronunciations could be English words
+//!
+//! The consonant clusters that can begin and end a syllable are learned from the dictionary:
+//! the onsets of the first syllables of words and the codas of their last ones. A cluster is
+//! attested when enough pronunciations have it, which keeps out the one-off clusters of names
+//! and loanwords, like the `Z M` of "zmijewski". Onsets and codas in the middle of a word are
+//! found by syllabifying it and checked against the same clusters.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use crate::core::{Stress, Symbol};
+use crate::syllable::syllabify;
+use crate::Cmudict;
+
+// how many pronunciations must have a cluster for it to be attested
+const MIN_COUNT: usize = 2;
+
+/// A part of a syllable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    /// The consonants before the vowel
+    Onset,
+    /// The vowel
+    Nucleus,
+    /// The consonants after the vowel
+    Coda,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Part::Onset => "onset",
+            Part::Nucleus => "nucleus",
+            Part::Coda => "coda",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A part of a pronunciation that isn't attested in the dictionary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    part: Part,
+    syllable: usize,
+    span: Range<usize>,
+    symbols: Vec<Symbol>,
+}
+
+impl Violation {
+    /// Returns which part of the syllable is illegal
+    pub fn part(&self) -> Part {
+        self.part
+    }
+
+    /// Returns the index of the syllable the part belongs to
+    pub fn syllable(&self) -> usize {
+        self.syllable
+    }
+
+    /// Returns the positions of the part's symbols in the pronunciation
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the symbols of the part
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.part == Part::Nucleus {
+            return write!(f, "no vowel");
+        }
+        write!(f, "illegal {} `", self.part)?;
+        for (idx, symbol) in self.symbols.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", symbol)?;
+        }
+        write!(f, "` in syllable {}", self.syllable + 1)
+    }
+}
+
+/// The onsets, vowels and codas of the syllables of a dictionary
+///
+/// The vowels are counted without their stress.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, Part, Phonotactics, Stress, Symbol};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let phonotactics = Phonotactics::new(&dict);
+///
+/// let blick = [Symbol::B, Symbol::L, Symbol::IH(Stress::Primary), Symbol::K];
+/// let bnick = [Symbol::B, Symbol::N, Symbol::IH(Stress::Primary), Symbol::K];
+/// assert!(phonotactics.is_legal(&blick));
+/// let violations = phonotactics.check(&bnick);
+/// assert_eq!(violations[0].part(), Part::Onset);
+/// assert_eq!(violations[0].span(), 0..2);
+/// assert!(phonotactics.score(&blick) > phonotactics.score(&bnick));
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Phonotactics {
+    onsets: HashMap<Vec<Symbol>, usize>,
+    nuclei: HashMap<Symbol, usize>,
+    codas: HashMap<Vec<Symbol>, usize>,
+    min_count: usize,
+}
+
+impl Phonotactics {
+    /// Learns the syllables of every pronunciation in a dictionary
+    pub fn new(dict: &Cmudict) -> Phonotactics {
+        Phonotactics::train_from(
+            dict.iter().flat_map(|(_, rules)| rules.iter().map(|rule| rule.pronunciation())),
+        )
+    }
+
+    /// Learns the syllables of pronunciations
+    pub fn train_from<'a, I>(pronunciations: I) -> Phonotactics
+    where
+        I: IntoIterator<Item = &'a [Symbol]>,
+    {
+        let mut phonotactics = Phonotactics {
+            onsets: HashMap::new(),
+            nuclei: HashMap::new(),
+            codas: HashMap::new(),
+            min_count: MIN_COUNT,
+        };
+        for phones in pronunciations {
+            let syllables = syllabify(phones);
+            let (first, last) = match (syllables.first(), syllables.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => continue,
+            };
+            *phonotactics.onsets.entry(first.onset().to_vec()).or_default() += 1;
+            *phonotactics.codas.entry(last.coda().to_vec()).or_default() += 1;
+            for syllable in &syllables {
+                *phonotactics.nuclei.entry(unstressed(syllable.nucleus())).or_default() += 1;
+            }
+        }
+        phonotactics
+    }
+
+    /// Sets how many pronunciations must have a cluster for it to be attested, two unless
+    /// this is called
+    ///
+    /// A higher count keeps out more of the rare clusters of names and loanwords, at the cost
+    /// of rare words like "depths". Small dictionaries need a count of one.
+    pub fn with_min_count(mut self, min_count: usize) -> Phonotactics {
+        self.min_count = min_count;
+        self
+    }
+
+    /// Returns `true` if the consonants are attested at the start of a syllable
+    pub fn is_onset(&self, consonants: &[Symbol]) -> bool {
+        self.onsets.get(consonants).is_some_and(|&c| c >= self.min_count)
+    }
+
+    /// Returns `true` if the consonants are attested at the end of a syllable
+    pub fn is_coda(&self, consonants: &[Symbol]) -> bool {
+        self.codas.get(consonants).is_some_and(|&c| c >= self.min_count)
+    }
+
+    /// Finds the parts of a pronunciation that aren't attested
+    ///
+    /// The pronunciation is split into syllables, and each onset and coda that isn't attested
+    /// is reported, in order. A pronunciation without a vowel has no syllables, and gets a
+    /// single violation of the nucleus covering all of it.
+    pub fn check(&self, phones: &[Symbol]) -> Vec<Violation> {
+        let syllables = syllabify(phones);
+        if syllables.is_empty() {
+            return vec![Violation {
+                part: Part::Nucleus,
+                syllable: 0,
+                span: 0..phones.len(),
+                symbols: phones.to_vec(),
+            }];
+        }
+        let mut violations = Vec::new();
+        let mut start = 0;
+        for (idx, syllable) in syllables.iter().enumerate() {
+            let (onset, coda) = (syllable.onset(), syllable.coda());
+            let nucleus = start + onset.len();
+            if !self.is_onset(onset) {
+                violations.push(Violation {
+                    part: Part::Onset,
+                    syllable: idx,
+                    span: start..nucleus,
+                    symbols: onset.to_vec(),
+                });
+            }
+            if !self.is_coda(coda) {
+                violations.push(Violation {
+                    part: Part::Coda,
+                    syllable: idx,
+                    span: nucleus + 1..nucleus + 1 + coda.len(),
+                    symbols: coda.to_vec(),
+                });
+            }
+            start = nucleus + 1 + coda.len();
+        }
+        violations
+    }
+
+    /// Returns `true` if every onset and coda of a pronunciation is attested
+    pub fn is_legal(&self, phones: &[Symbol]) -> bool {
+        self.check(phones).is_empty()
+    }
+
+    /// Scores how well-formed a pronunciation is, as the mean log probability of its
+    /// syllables
+    ///
+    /// The probability of a syllable is that of its onset, its vowel and its coda, each
+    /// smoothed so clusters that were never seen are unlikely rather than impossible. Higher
+    /// scores are more English-like. A pronunciation without a vowel scores negative
+    /// infinity.
+    pub fn score(&self, phones: &[Symbol]) -> f64 {
+        let syllables = syllabify(phones);
+        if syllables.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+        let total: f64 = syllables
+            .iter()
+            .map(|syllable| {
+                log_probability(&self.onsets, syllable.onset())
+                    + log_probability(&self.nuclei, &unstressed(syllable.nucleus()))
+                    + log_probability(&self.codas, syllable.coda())
+            })
+            .sum();
+        total / syllables.len() as f64
+    }
+
+    /// Returns the attested onsets with how many pronunciations have them
+    pub(crate) fn onsets(&self) -> impl Iterator<Item = (&[Symbol], usize)> + '_ {
+        attested(&self.onsets, self.min_count)
+    }
+
+    /// Returns the attested codas with how many pronunciations have them
+    pub(crate) fn codas(&self) -> impl Iterator<Item = (&[Symbol], usize)> + '_ {
+        attested(&self.codas, self.min_count)
+    }
+}
+
+fn unstressed(symbol: Symbol) -> Symbol {
+    symbol.with_stress(Stress::None)
+}
+
+fn attested(
+    counts: &HashMap<Vec<Symbol>, usize>,
+    min_count: usize,
+) -> impl Iterator<Item = (&[Symbol], usize)> + '_ {
+    counts
+        .iter()
+        .filter(move |&(_, &count)| count >= min_count)
+        .map(|(cluster, &count)| (cluster.as_slice(), count))
+}
+
+// add-one smoothed, with one more type for everything that was never seen
+fn log_probability<K, Q>(counts: &HashMap<K, usize>, key: &Q) -> f64
+where
+    K: std::borrow::Borrow<Q> + std::hash::Hash + Eq,
+    Q: std::hash::Hash + Eq + ?Sized,
+{
+    let total: usize = counts.values().sum();
+    let count = counts.get(key).copied().unwrap_or(0);
+    ((count + 1) as f64 / (total + counts.len() + 1) as f64).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phones(s: &str) -> Vec<Symbol> {
+        s.split_whitespace().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn legality() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let p = Phonotactics::new(&d);
+        for word in &["blick", "strengths", "extra", "abstract", "finger"] {
+            let rule = &d.get(word).unwrap()[0];
+            let violations = p.check(rule.pronunciation());
+            assert!(violations.is_empty(), "{} has {:?}", word, violations);
+        }
+        assert!(p.is_legal(&phones("S P L AA1 R K")));
+
+        let ngap = p.check(&phones("NG AE1 P"));
+        assert_eq!(ngap.len(), 1);
+        assert_eq!((ngap[0].part(), ngap[0].syllable(), ngap[0].span()), (Part::Onset, 0, 0..1));
+
+        let bad = p.check(&phones("Z M AE1 T K"));
+        assert_eq!(bad.len(), 2);
+        assert_eq!((bad[0].part(), bad[0].span()), (Part::Onset, 0..2));
+        assert_eq!(bad[1].to_string(), "illegal coda `T K` in syllable 1");
+
+        let none = p.check(&phones("P S T"));
+        assert_eq!((none[0].part(), none[0].span()), (Part::Nucleus, 0..3));
+        assert!(!p.is_legal(&[]));
+    }
+
+    #[test]
+    fn scores() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let p = Phonotactics::new(&d);
+        let blick = p.score(&phones("B L IH1 K"));
+        assert!(blick > p.score(&phones("B W IH1 K")));
+        assert!(p.score(&phones("B W IH1 K")) > p.score(&phones("B N IH1 K Z D")));
+        assert!(blick.is_finite());
+        assert_eq!(p.score(&phones("S T")), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn min_count() {
+        let d: Cmudict = "vlad V L AE1 D\ncat K AE1 T\n".parse().unwrap();
+        let p = Phonotactics::new(&d);
+        assert!(!p.is_legal(&phones("K AE1 T")));
+        let p = p.with_min_count(1);
+        assert!(p.is_legal(&phones("V L AE1 T")));
+        assert!(!p.is_legal(&phones("K L AE1 D")));
+        assert_eq!(p.onsets().count(), 2);
+    }
+}
@@ -0,0 +1,369 @@
+//! Making up pronounceable words that aren't in the dictionary
+//!
+//! Pseudowords are built a syllable at a time from the onsets, vowels and codas of the
+//! dictionary, each chosen as often as the dictionary uses it, so common clusters come up more
+//! than rare ones. Whatever is made up is syllabified again and checked with `Phonotactics`,
+//! and thrown away if its syllables come out differently, if it isn't legal, or if it sounds
+//! like a word that's already in the dictionary.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{Stress, Symbol};
+use crate::lookup::Source;
+use crate::p2g::Speller;
+use crate::phonotactics::Phonotactics;
+use crate::syllable::{syllabify, Syllable};
+use crate::Cmudict;
+
+// how many pseudowords are made up before giving up on finding one that will do
+const MAX_TRIES: usize = 1000;
+// how many spellings are asked for, to find one that isn't already a word
+const SPELLINGS: usize = 5;
+// the seed used unless another is given
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// A made up word
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pseudoword {
+    pronunciation: Vec<Symbol>,
+    spelling: Option<String>,
+}
+
+impl Pseudoword {
+    /// Returns the symbols of the pseudoword
+    pub fn pronunciation(&self) -> &[Symbol] {
+        &self.pronunciation
+    }
+
+    /// Splits the pseudoword into syllables
+    pub fn syllables(&self) -> Vec<Syllable> {
+        syllabify(&self.pronunciation)
+    }
+
+    /// Returns the spelling of the pseudoword, if it was made with a `Speller`
+    pub fn spelling(&self) -> Option<&str> {
+        self.spelling.as_deref()
+    }
+}
+
+// the stress of a syllable to make, and how many consonants its onset and coda have if they
+// have to match a word's
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    stress: Stress,
+    onset: Option<usize>,
+    coda: Option<usize>,
+}
+
+/// Makes up pseudowords from the syllables of a dictionary
+///
+/// The pseudowords are random, but the same seed always makes the same ones from the same
+/// dictionary.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, Phonotactics, Pseudowords, Stress};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let mut pseudowords = Pseudowords::new(&dict).with_seed(7);
+///
+/// let trochee = pseudowords.generate(&[Stress::Primary, Stress::None]).unwrap();
+/// assert_eq!(trochee.syllables().len(), 2);
+/// assert!(Phonotactics::new(&dict).is_legal(trochee.pronunciation()));
+///
+/// // the same stress and the same number of consonants in every onset and coda as "basket"
+/// let basket = dict.get("basket").unwrap()[0].pronunciation();
+/// let matched = pseudowords.matching(basket).unwrap();
+/// assert_eq!(matched.pronunciation().len(), basket.len());
+/// assert_ne!(matched.pronunciation(), basket);
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pseudowords<'a> {
+    dict: &'a Cmudict,
+    phonotactics: Phonotactics,
+    onsets: Vec<(Vec<Symbol>, usize)>,
+    nuclei: Vec<(Symbol, usize)>,
+    codas: Vec<(Vec<Symbol>, usize)>,
+    // every pronunciation in the dictionary, without stress
+    homophones: HashSet<Vec<Symbol>>,
+    speller: Option<Speller>,
+    state: u64,
+}
+
+impl<'a> Pseudowords<'a> {
+    /// Learns the syllables of every pronunciation in a dictionary
+    pub fn new(dict: &'a Cmudict) -> Pseudowords<'a> {
+        Pseudowords::with_phonotactics(dict, Phonotactics::new(dict))
+    }
+
+    /// Makes up pseudowords from the onsets and codas attested in `phonotactics`, and the
+    /// vowels of a dictionary
+    ///
+    /// This is for choosing how many times a cluster has to be seen to be used, with
+    /// `Phonotactics::with_min_count`.
+    pub fn with_phonotactics(dict: &'a Cmudict, phonotactics: Phonotactics) -> Pseudowords<'a> {
+        let mut homophones = HashSet::new();
+        let mut nuclei: HashMap<Symbol, usize> = HashMap::new();
+        for (_, rules) in dict.iter() {
+            for rule in rules {
+                let phones = rule.pronunciation();
+                homophones.insert(unstressed(phones));
+                for &vowel in phones.iter().filter(|s| s.is_syllable()) {
+                    *nuclei.entry(vowel).or_default() += 1;
+                }
+            }
+        }
+        let mut pseudowords = Pseudowords {
+            dict,
+            onsets: phonotactics.onsets().map(|(onset, n)| (onset.to_vec(), n)).collect(),
+            nuclei: nuclei.into_iter().collect(),
+            codas: phonotactics.codas().map(|(coda, n)| (coda.to_vec(), n)).collect(),
+            phonotactics,
+            homophones,
+            speller: None,
+            state: SEED,
+        };
+        // the counts come out of hash maps, so they are put in order for a seed to always
+        // choose the same things
+        let key = |phones: &[Symbol]| phones.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        pseudowords.onsets.sort_by_cached_key(|(onset, _)| key(onset));
+        pseudowords.nuclei.sort_by_cached_key(|(vowel, _)| vowel.to_string());
+        pseudowords.codas.sort_by_cached_key(|(coda, _)| key(coda));
+        pseudowords
+    }
+
+    /// Sets the seed of the random choices
+    pub fn with_seed(mut self, seed: u64) -> Pseudowords<'a> {
+        self.state = seed;
+        self
+    }
+
+    /// Spells every pseudoword with a speller, skipping those it can only spell as words that
+    /// are already in the dictionary, or with letters other than `a` to `z`
+    pub fn with_speller(mut self, speller: Speller) -> Pseudowords<'a> {
+        self.speller = Some(speller);
+        self
+    }
+
+    /// Makes up a pseudoword with one syllable for each stress, in order
+    ///
+    /// Returns `None` if no stresses are given, or if nothing that will do can be made up,
+    /// which happens when the dictionary is too small to have many syllables to choose from.
+    pub fn generate(&mut self, stresses: &[Stress]) -> Option<Pseudoword> {
+        let shapes: Vec<Shape> = stresses
+            .iter()
+            .map(|&stress| Shape {
+                stress,
+                onset: None,
+                coda: None,
+            })
+            .collect();
+        self.make(&shapes, None)
+    }
+
+    /// Makes up a pseudoword that matches a pronunciation's syllables: the same stress on each,
+    /// and the same number of consonants in each onset and coda
+    ///
+    /// The pseudoword has as many symbols as the pronunciation, and is never the same as it,
+    /// even if the pronunciation isn't in the dictionary. Returns `None` if the pronunciation
+    /// has no vowels, or if nothing that will do can be made up.
+    pub fn matching(&mut self, phones: &[Symbol]) -> Option<Pseudoword> {
+        let shapes: Vec<Shape> = syllabify(phones)
+            .iter()
+            .map(|syllable| Shape {
+                stress: syllable.stress(),
+                onset: Some(syllable.onset().len()),
+                coda: Some(syllable.coda().len()),
+            })
+            .collect();
+        self.make(&shapes, Some(unstressed(phones)))
+    }
+
+    fn make(&mut self, shapes: &[Shape], exclude: Option<Vec<Symbol>>) -> Option<Pseudoword> {
+        if shapes.is_empty() {
+            return None;
+        }
+        'tries: for _ in 0..MAX_TRIES {
+            let mut phones = Vec::new();
+            let mut clusters = Vec::with_capacity(shapes.len());
+            for shape in shapes {
+                let onset = choose(&mut self.state, &self.onsets, |onset| {
+                    shape.onset.is_none() || shape.onset == Some(onset.len())
+                });
+                let nucleus = choose(&mut self.state, &self.nuclei, |vowel| {
+                    vowel.stress() == Some(shape.stress)
+                });
+                let coda = choose(&mut self.state, &self.codas, |coda| {
+                    shape.coda.is_none() || shape.coda == Some(coda.len())
+                });
+                let (onset, nucleus, coda) = match (onset, nucleus, coda) {
+                    (Some(onset), Some(&nucleus), Some(coda)) => (onset, nucleus, coda),
+                    _ => return None,
+                };
+                phones.extend(onset);
+                phones.push(nucleus);
+                phones.extend(coda);
+                clusters.push((onset, coda));
+            }
+
+            // the consonants between two vowels might split differently than they were chosen
+            let syllables = syllabify(&phones);
+            let same = syllables.iter().zip(&clusters).all(|(syllable, (onset, coda))| {
+                syllable.onset() == onset.as_slice() && syllable.coda() == coda.as_slice()
+            });
+            if !same || !self.phonotactics.is_legal(&phones) {
+                continue;
+            }
+            let sound = unstressed(&phones);
+            if self.homophones.contains(&sound) || exclude.as_ref() == Some(&sound) {
+                continue;
+            }
+
+            let spelling = match &self.speller {
+                Some(speller) => {
+                    let spellings = speller.spell(&phones, SPELLINGS);
+                    // spellings like "sa's" come from the possessives in the dictionary
+                    let new = spellings.into_iter().find(|s| {
+                        s.source() == Source::Predicted
+                            && s.spelling().chars().all(|c| c.is_ascii_alphabetic())
+                            && self.dict.get(s.spelling()).is_none()
+                    });
+                    match new {
+                        Some(spelling) => Some(spelling.spelling().to_string()),
+                        None => continue 'tries,
+                    }
+                }
+                None => None,
+            };
+            return Some(Pseudoword {
+                pronunciation: phones,
+                spelling,
+            });
+        }
+        None
+    }
+}
+
+fn unstressed(phones: &[Symbol]) -> Vec<Symbol> {
+    phones.iter().map(|s| s.with_stress(Stress::None)).collect()
+}
+
+// chooses one of the items that `keep` allows, each as often as its count, or `None` if it
+// allows none of them
+fn choose<'t, T, F>(state: &mut u64, items: &'t [(T, usize)], keep: F) -> Option<&'t T>
+where
+    F: Fn(&T) -> bool,
+{
+    let total: usize = items.iter().filter(|(item, _)| keep(item)).map(|(_, c)| c).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut target = (next(state) % total as u64) as usize;
+    for (item, count) in items.iter().filter(|(item, _)| keep(item)) {
+        if target < *count {
+            return Some(item);
+        }
+        target -= count;
+    }
+    None
+}
+
+// splitmix64, which is plenty for choosing syllables
+fn next(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudowords() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let phonotactics = Phonotactics::new(&d);
+        let mut pseudowords = Pseudowords::new(&d);
+        let stresses = [Stress::None, Stress::Primary, Stress::None];
+        for _ in 0..20 {
+            let word = pseudowords.generate(&stresses).unwrap();
+            let phones = word.pronunciation();
+            let made: Vec<Stress> = word.syllables().iter().map(|s| s.stress()).collect();
+            assert_eq!(made, stresses);
+            assert!(phonotactics.is_legal(phones));
+            assert!(!pseudowords.homophones.contains(&unstressed(phones)));
+            assert_eq!(word.spelling(), None);
+        }
+
+        let target = d.get("window").unwrap()[0].pronunciation();
+        let shape = |phones: &[Symbol]| -> Vec<(usize, Stress, usize)> {
+            syllabify(phones)
+                .iter()
+                .map(|s| (s.onset().len(), s.stress(), s.coda().len()))
+                .collect()
+        };
+        for _ in 0..20 {
+            let word = pseudowords.matching(target).unwrap();
+            assert_eq!(shape(word.pronunciation()), shape(target));
+        }
+
+        assert_eq!(pseudowords.generate(&[]), None);
+        assert_eq!(pseudowords.matching(&[Symbol::S, Symbol::T]), None);
+    }
+
+    #[test]
+    fn seeds() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let stresses = [Stress::Primary, Stress::None];
+        let made = |seed: u64| {
+            let mut pseudowords = Pseudowords::new(&d).with_seed(seed);
+            (0..5).map(|_| pseudowords.generate(&stresses).unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(made(1), made(1));
+        assert_ne!(made(1), made(2));
+    }
+
+    #[test]
+    fn spellings() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let speller = Speller::train_from(crate::sample(&d, &["ba", "be", "bl"]));
+        let mut pseudowords = Pseudowords::new(&d).with_speller(speller);
+        for _ in 0..10 {
+            let word = pseudowords.generate(&[Stress::Primary]).unwrap();
+            let spelling = word.spelling().unwrap();
+            assert!(!spelling.is_empty() && spelling.chars().all(|c| c.is_ascii_lowercase()));
+            assert!(d.get(spelling).is_none());
+        }
+    }
+
+    #[test]
+    fn small_dictionaries() {
+        use Symbol::*;
+        let d: Cmudict = "bat B AE1 T\nsip S IH1 P\n".parse().unwrap();
+        // with the default count, nothing in a dictionary this small is attested
+        assert_eq!(Pseudowords::new(&d).generate(&[Stress::Primary]), None);
+
+        let phonotactics = Phonotactics::new(&d).with_min_count(1);
+        let mut pseudowords = Pseudowords::with_phonotactics(&d, phonotactics);
+        let made = pseudowords.generate(&[Stress::Primary]).unwrap();
+        let sound = made.pronunciation();
+        assert!([B, S].contains(&sound[0]) && [T, P].contains(&sound[2]));
+        assert_ne!(sound, [B, AE(Stress::Primary), T]);
+        assert_ne!(sound, [S, IH(Stress::Primary), P]);
+    }
+}
@@ -0,0 +1,265 @@
+//! "Did you mean" suggestions for misspelled words
+//!
+//! The words of the dictionary are walked in alphabetical order as if they were a trie: words
+//! that share a prefix share the rows of the edit distance table for it, and once every entry
+//! of a row is over the limit, every word starting with that prefix is skipped at once.
+//! Transposing two adjacent letters counts as a single edit.
+
+use crate::core::Rule;
+use crate::distance::PhoneticDistance;
+use crate::g2p::Predictor;
+use crate::Cmudict;
+
+/// A word suggested in place of one that isn't in the dictionary
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion<'a> {
+    word: &'a str,
+    rules: &'a [Rule],
+    distance: usize,
+    phonetic: Option<f64>,
+}
+
+impl<'a> Suggestion<'a> {
+    /// Returns the suggested word
+    pub fn word(&self) -> &'a str {
+        self.word
+    }
+
+    /// Returns the pronunciations of the suggested word
+    pub fn rules(&self) -> &'a [Rule] {
+        self.rules
+    }
+
+    /// Returns the number of letters that have to be inserted, deleted, substituted or
+    /// transposed to spell the suggestion
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    /// Returns how far the closest pronunciation of the suggestion is from the guessed
+    /// pronunciation of the misspelling, if the suggestions were ranked by sound
+    pub fn phonetic_distance(&self) -> Option<f64> {
+        self.phonetic
+    }
+}
+
+impl Cmudict {
+    /// Suggests words spelled like `word`, no more than `max_distance` edits away
+    ///
+    /// The suggestions are ordered by their distance and then alphabetically. A word that is
+    /// in the dictionary is its own suggestion, with a distance of zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::Cmudict;
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// let suggestions = dict.suggest("pronounciation", 1);
+    /// assert_eq!(suggestions[0].word(), "pronunciation");
+    /// assert_eq!(suggestions[0].distance(), 1);
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn suggest(&self, word: &str, max_distance: usize) -> Vec<Suggestion<'_>> {
+        let mut suggestions = self.within_edits(word, max_distance);
+        suggestions.sort_by(|a, b| (a.distance, a.word).cmp(&(b.distance, b.word)));
+        suggestions
+    }
+
+    /// Suggests words spelled like `word`, ranked by how close they sound to it
+    ///
+    /// The pronunciation of `word` is guessed with `predictor`, and the suggestions are
+    /// ordered by the phonetic distance of their closest pronunciation from the guess, then
+    /// by their spelling distance, and then alphabetically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Cmudict, LetterRules};
+    /// # use cmudict::Result;
+    /// #
+    /// # fn main() {
+    /// #   if let Err(_) = run() {
+    /// #     panic!("error!");
+    /// #   }
+    /// # }
+    /// # fn run() -> Result<()> {
+    ///
+    /// let dict = Cmudict::new("./resources/cmudict.dict")?;
+    /// // "asics" and "basics" are spelled as close, but "physics" sounds the closest
+    /// assert_eq!(dict.suggest("fysics", 2)[0].word(), "asics");
+    /// let suggestions = dict.suggest_by_sound("fysics", 2, &LetterRules::english());
+    /// assert_eq!(suggestions[0].word(), "physics");
+    ///
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn suggest_by_sound<P>(
+        &self,
+        word: &str,
+        max_distance: usize,
+        predictor: &P,
+    ) -> Vec<Suggestion<'_>>
+    where
+        P: Predictor + ?Sized,
+    {
+        let guess = predictor.predict(&word.to_lowercase());
+        let weights = PhoneticDistance {
+            ignore_stress: true,
+            ..PhoneticDistance::default()
+        };
+        let mut suggestions = self.within_edits(word, max_distance);
+        for suggestion in &mut suggestions {
+            suggestion.phonetic = suggestion
+                .rules
+                .iter()
+                .map(|rule| weights.distance(&guess, rule.pronunciation()))
+                .min_by(|a, b| a.partial_cmp(b).unwrap());
+        }
+        suggestions.sort_by(|a, b| {
+            a.phonetic
+                .partial_cmp(&b.phonetic)
+                .unwrap()
+                .then_with(|| (a.distance, a.word).cmp(&(b.distance, b.word)))
+        });
+        suggestions
+    }
+
+    // every word within `max_distance` edits, in alphabetical order
+    fn within_edits(&self, word: &str, max_distance: usize) -> Vec<Suggestion<'_>> {
+        let query: Vec<char> = word.chars().collect();
+        let n = query.len();
+        let sorted = &self.labels().sorted;
+
+        let mut found = Vec::new();
+        // the letters of the current prefix and the row of the table after each of them
+        let mut prefix: Vec<char> = Vec::new();
+        let mut rows: Vec<Vec<usize>> = vec![(0..=n).collect()];
+        let mut idx = 0;
+        while idx < sorted.len() {
+            let label: Vec<char> = sorted[idx].chars().collect();
+            let common = prefix
+                .iter()
+                .zip(&label)
+                .take_while(|(a, b)| a == b)
+                .count();
+            prefix.truncate(common);
+            rows.truncate(common + 1);
+
+            let mut pruned = false;
+            for &c in &label[common..] {
+                let i = prefix.len() + 1;
+                let previous = &rows[i - 1];
+                let mut row = Vec::with_capacity(n + 1);
+                row.push(i);
+                for j in 1..=n {
+                    let substitution = previous[j - 1] + usize::from(query[j - 1] != c);
+                    let mut cost = substitution.min(previous[j] + 1).min(row[j - 1] + 1);
+                    if i > 1 && j > 1 && query[j - 1] == prefix[i - 2] && query[j - 2] == c {
+                        cost = cost.min(rows[i - 2][j - 2] + 1);
+                    }
+                    row.push(cost);
+                }
+                prefix.push(c);
+                // the next row can only do better than this one with a transposition from
+                // the row before
+                let best = row.iter().min().copied().unwrap_or(0);
+                let before = previous.iter().min().copied().unwrap_or(0);
+                rows.push(row);
+                if best > max_distance && before >= max_distance {
+                    pruned = true;
+                    break;
+                }
+            }
+
+            if pruned {
+                // skip every word starting with the prefix
+                let stem: String = prefix.iter().collect();
+                idx += sorted[idx..].partition_point(|w| w.starts_with(&stem));
+                continue;
+            }
+            let distance = rows[label.len()][n];
+            if distance <= max_distance {
+                if let Some((word, rules)) = self.map.get_key_value(&sorted[idx]) {
+                    found.push(Suggestion {
+                        word,
+                        rules,
+                        distance,
+                        phonetic: None,
+                    });
+                }
+            }
+            idx += 1;
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::g2p::LetterRules;
+
+    #[test]
+    fn suggesting() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+
+        let words = |s: &[Suggestion]| -> Vec<String> {
+            s.iter().map(|s| s.word().to_string()).collect()
+        };
+        let cat = d.suggest("cat", 0);
+        assert_eq!(words(&cat), vec!["cat"]);
+
+        // a transposition is one edit
+        let teh = d.suggest("teh", 1);
+        assert!(words(&teh).contains(&"the".to_string()));
+        assert!(teh.iter().all(|s| s.distance() <= 1));
+        assert!(teh.windows(2).all(|w| w[0].distance() <= w[1].distance()));
+
+        // the same as checking every word in the dictionary
+        let brute: usize = d
+            .iter()
+            .filter(|(word, _)| osa(word, "recieve") <= 2)
+            .count();
+        assert_eq!(d.suggest("recieve", 2).len(), brute);
+        assert!(words(&d.suggest("recieve", 2)).contains(&"receive".to_string()));
+
+        let by_sound = d.suggest_by_sound("nite", 2, &LetterRules::english());
+        assert!(by_sound[0].phonetic_distance().is_some());
+        assert!(by_sound
+            .windows(2)
+            .all(|w| w[0].phonetic_distance() <= w[1].phonetic_distance()));
+    }
+
+    // optimal string alignment distance, the slow way
+    fn osa(a: &str, b: &str) -> usize {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for (i, row) in d.iter_mut().enumerate() {
+            row[0] = i;
+        }
+        d[0] = (0..=b.len()).collect();
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+                if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                    d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+                }
+            }
+        }
+        d[a.len()][b.len()]
+    }
+}
@@ -0,0 +1,159 @@
+//! Splitting pronunciations into syllables
+//!
+//! Every vowel is the nucleus of its own syllable. The consonants between two vowels are split
+//! using the maximal onset principle: the next syllable takes as many of them as can begin an
+//! English word, and the rest close the syllable before.
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Rule, Stress, Symbol};
+
+/// A single syllable of a pronunciation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Syllable {
+    onset: Vec<Symbol>,
+    nucleus: Symbol,
+    coda: Vec<Symbol>,
+}
+
+impl Syllable {
+    /// Returns the consonants before the vowel
+    pub fn onset(&self) -> &[Symbol] {
+        &self.onset
+    }
+
+    /// Returns the vowel at the center of the syllable
+    pub fn nucleus(&self) -> Symbol {
+        self.nucleus
+    }
+
+    /// Returns the consonants after the vowel
+    pub fn coda(&self) -> &[Symbol] {
+        &self.coda
+    }
+
+    /// Returns the stress of the syllable's vowel
+    pub fn stress(&self) -> Stress {
+        self.nucleus.stress().unwrap_or(Stress::None)
+    }
+
+    /// Iterates over every symbol of the syllable in order
+    pub fn symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.onset
+            .iter()
+            .copied()
+            .chain(Some(self.nucleus))
+            .chain(self.coda.iter().copied())
+    }
+}
+
+/// Splits a pronunciation into syllables
+///
+/// A pronunciation without any vowels has no syllables, so the result is empty.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{syllabify, Symbol, Stress};
+///
+/// let extra = [
+///     Symbol::EH(Stress::Primary),
+///     Symbol::K,
+///     Symbol::S,
+///     Symbol::T,
+///     Symbol::R,
+///     Symbol::AH(Stress::None),
+/// ];
+/// let syllables = syllabify(&extra);
+/// assert_eq!(syllables.len(), 2);
+/// assert_eq!(syllables[0].coda(), &[Symbol::K]);
+/// assert_eq!(syllables[1].onset(), &[Symbol::S, Symbol::T, Symbol::R]);
+/// ```
+pub fn syllabify(phones: &[Symbol]) -> Vec<Syllable> {
+    let nuclei: Vec<usize> = phones
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.is_syllable())
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut syllables: Vec<Syllable> = Vec::with_capacity(nuclei.len());
+    let mut start = 0;
+    for (n, &nucleus) in nuclei.iter().enumerate() {
+        let end = match nuclei.get(n + 1) {
+            Some(&next) => {
+                // the next onset is the longest legal run of consonants before the next vowel
+                let between = &phones[nucleus + 1..next];
+                let split = (0..=between.len())
+                    .find(|&i| is_onset(&between[i..]))
+                    .unwrap_or(between.len());
+                nucleus + 1 + split
+            }
+            None => phones.len(),
+        };
+        syllables.push(Syllable {
+            onset: phones[start..nucleus].to_vec(),
+            nucleus: phones[nucleus],
+            coda: phones[nucleus + 1..end].to_vec(),
+        });
+        start = end;
+    }
+    syllables
+}
+
+impl Rule {
+    /// Splits the pronunciation into syllables
+    pub fn syllables(&self) -> Vec<Syllable> {
+        syllabify(self.pronunciation())
+    }
+}
+
+/// Returns `true` if the consonants can begin a syllable of English
+///
+/// An empty onset is always allowed.
+pub(crate) fn is_onset(consonants: &[Symbol]) -> bool {
+    use crate::core::Symbol::*;
+    match consonants {
+        [] => true,
+        [NG] => false,
+        [s] => !s.is_syllable(),
+        [P, L] | [P, R] | [B, L] | [B, R] | [K, L] | [K, R] | [G, L] | [G, R] => true,
+        [F, L] | [F, R] | [TH, R] | [SH, R] | [T, R] | [D, R] | [V, R] => true,
+        [T, W] | [D, W] | [K, W] | [G, W] | [S, W] | [TH, W] => true,
+        [S, P] | [S, T] | [S, K] | [S, M] | [S, N] | [S, L] | [S, F] => true,
+        [P, Y] | [B, Y] | [F, Y] | [V, Y] | [K, Y] | [G, Y] | [M, Y] | [HH, Y] => true,
+        [S, P, R] | [S, P, L] | [S, T, R] | [S, K, R] | [S, K, L] | [S, K, W] => true,
+        [S, P, Y] | [S, K, Y] => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Symbol::*;
+
+    #[test]
+    fn syllables() {
+        // "abstract"
+        let abstract_ = [AE(Stress::Primary), B, S, T, R, AE(Stress::Secondary), K, T];
+        let syllables = syllabify(&abstract_);
+        assert_eq!(syllables.len(), 2);
+        assert_eq!(syllables[0].onset(), &[]);
+        assert_eq!(syllables[0].coda(), &[B]);
+        assert_eq!(syllables[1].onset(), &[S, T, R]);
+        assert_eq!(syllables[1].coda(), &[K, T]);
+        assert_eq!(syllables[1].stress(), Stress::Secondary);
+
+        // "singer" keeps NG in the first syllable
+        let singer = syllabify(&[S, IH(Stress::Primary), NG, ER(Stress::None)]);
+        assert_eq!(singer[0].coda(), &[NG]);
+        assert_eq!(singer[1].onset(), &[]);
+
+        let all: Vec<Symbol> = syllables.iter().flat_map(|s| s.symbols()).collect();
+        assert_eq!(all, abstract_.to_vec());
+        assert!(syllabify(&[HH, M]).is_empty());
+    }
+}
@@ -0,0 +1,764 @@
+//! Turning running text into pronunciations
+//!
+//! Text is split on whitespace and dashes into tokens, and the punctuation around each token
+//! decides how strong the boundary after it is. Each token is then looked up as it is, as an
+//! abbreviation, as a number, as the parts of a hyphenated compound, or as a stem with a
+//! contraction or possessive, before its pronunciation is guessed.
+
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::acronym::Acronyms;
+use crate::core::{Rule, Stress, Symbol};
+use crate::g2p::Predictor;
+use crate::lookup::Source;
+use crate::morphology::s_suffix;
+use crate::normalize::{verbalize, EnglishHints, Hints};
+use crate::variant::Policy;
+use crate::Cmudict;
+
+// abbreviations that are read as other words, without their final period
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("approx", "approximately"),
+    ("apr", "april"),
+    ("aug", "august"),
+    ("ave", "avenue"),
+    ("blvd", "boulevard"),
+    ("capt", "captain"),
+    ("corp", "corporation"),
+    ("dec", "december"),
+    ("dept", "department"),
+    ("dr", "doctor"),
+    ("e.g", "for example"),
+    ("etc", "et cetera"),
+    ("feb", "february"),
+    ("govt", "government"),
+    ("i.e", "that is"),
+    ("inc", "incorporated"),
+    ("jr", "junior"),
+    ("jul", "july"),
+    ("jun", "june"),
+    ("ltd", "limited"),
+    ("mr", "mister"),
+    ("mrs", "missus"),
+    ("mt", "mount"),
+    ("nov", "november"),
+    ("oct", "october"),
+    ("prof", "professor"),
+    ("sep", "september"),
+    ("sept", "september"),
+    ("sgt", "sergeant"),
+    ("sr", "senior"),
+    ("st", "street"),
+    ("vs", "versus"),
+];
+
+// symbols that start amounts of money
+const CURRENCIES: &str = "$\u{a3}\u{20ac}";
+
+// symbols that are read as words when they stand alone
+const SYMBOLS: &[(char, &str)] = &[
+    ('&', "and"),
+    ('+', "plus"),
+    ('@', "at"),
+    ('%', "percent"),
+    ('=', "equals"),
+];
+
+/// How strongly a word is separated from the one after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Boundary {
+    /// Nothing but a space, or a dash within a compound
+    Word,
+    /// A comma, colon, semicolon, bracket, dash or ellipsis
+    Phrase,
+    /// A full stop, question mark or exclamation mark, or the end of the text
+    Sentence,
+}
+
+/// A word of a transcribed text and the pronunciation chosen for it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Word {
+    text: String,
+    span: Range<usize>,
+    rule: Rule,
+    source: Source,
+    boundary: Boundary,
+}
+
+impl Word {
+    /// Returns the word that was pronounced, in lowercase
+    ///
+    /// This is what the text was expanded to, so it is "twelve" for "12" and "mister" for
+    /// "Mr.".
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte range of the text the word was read from
+    ///
+    /// Every word a token expands to has the span of the whole token, so the words of "12.5"
+    /// all have the same span.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the pronunciation chosen for the word
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Returns the symbols of the pronunciation chosen for the word
+    pub fn pronunciation(&self) -> &[Symbol] {
+        self.rule.pronunciation()
+    }
+
+    /// Returns where the pronunciation came from
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Returns how strongly the word is separated from the next one
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+}
+
+/// The words of a text along with their pronunciations
+///
+/// Displaying a transcription writes the symbols of each word separated by spaces, with a
+/// `|` between words.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transcription {
+    words: Vec<Word>,
+}
+
+impl Transcription {
+    /// Returns the words in the order they were read
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    /// Returns the symbols of every word, one after another
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.words
+            .iter()
+            .flat_map(|word| word.pronunciation().iter().copied())
+            .collect()
+    }
+
+    /// Consumes the transcription, returning its words
+    pub fn into_words(self) -> Vec<Word> {
+        self.words
+    }
+}
+
+impl fmt::Display for Transcription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, word) in self.words.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " | ")?;
+            }
+            for (jdx, symbol) in word.pronunciation().iter().enumerate() {
+                if jdx > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", symbol)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Transcribes running text with a dictionary, guessing the words it doesn't have
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Boundary, Cmudict, LetterRules, Source, Transcriber};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let rules = LetterRules::english();
+/// let transcriber = Transcriber::new(&dict, &rules);
+///
+/// let text = "Dr. Smith's blue-green cat ate 12 fish on Oct. 17, 2026.";
+/// let transcription = transcriber.transcribe(text);
+/// let words: Vec<&str> = transcription.words().iter().map(|w| w.text()).collect();
+/// assert_eq!(
+///     words,
+///     vec![
+///         "doctor", "smith's", "blue", "green", "cat", "ate", "twelve", "fish", "on",
+///         "october", "seventeenth", "twenty", "twenty", "six",
+///     ]
+/// );
+///
+/// let twelve = &transcription.words()[6];
+/// assert_eq!(&text[twelve.span()], "12");
+/// assert_eq!(twelve.source(), Source::Dictionary);
+/// assert_eq!(transcription.words()[10].boundary(), Boundary::Phrase);
+/// assert_eq!(transcription.words()[13].boundary(), Boundary::Sentence);
+///
+/// #   Ok(())
+/// # }
+/// ```
+pub struct Transcriber<'a> {
+    dict: &'a Cmudict,
+    predictor: &'a dyn Predictor,
+    hints: &'a dyn Hints,
+    acronyms: Option<&'a Acronyms>,
+    policy: Policy<'a>,
+}
+
+// a token of the text, with the punctuation either side of it
+struct Token<'t> {
+    offset: usize,
+    leading: &'t str,
+    text: &'t str,
+    trailing: &'t str,
+}
+
+// what is known about the text around the token being read
+#[derive(Clone, Copy)]
+struct Context<'t> {
+    previous: Option<&'t str>,
+    next: Option<&'t str>,
+    // the whole text is in capitals, so none of it is an acronym
+    shouting: bool,
+}
+
+impl<'a> Transcriber<'a> {
+    /// Creates a transcriber that looks words up in `dict`, and guesses the pronunciations
+    /// of the words it can't find with `predictor`
+    ///
+    /// Numbers, dates and amounts of money are read with `verbalize`, using `EnglishHints`
+    /// to decide how plain numbers are read. Acronyms written in capitals are spelled out or
+    /// read as words as `Acronyms::reading` guesses, unless the whole text is in capitals.
+    pub fn new(dict: &'a Cmudict, predictor: &'a dyn Predictor) -> Transcriber<'a> {
+        Transcriber {
+            dict,
+            predictor,
+            hints: &EnglishHints,
+            acronyms: None,
+            policy: Policy::First,
+        }
+    }
+
+    /// Reads numbers with `hints` rather than `EnglishHints`
+    pub fn with_hints(mut self, hints: &'a dyn Hints) -> Transcriber<'a> {
+        self.hints = hints;
+        self
+    }
+
+    /// Reads acronyms with `acronyms`, rather than guessing how every acronym is read
+    pub fn with_acronyms(mut self, acronyms: &'a Acronyms) -> Transcriber<'a> {
+        self.acronyms = Some(acronyms);
+        self
+    }
+
+    /// Chooses between the pronunciations of words with more than one with `policy`, rather
+    /// than taking the first
+    pub fn with_policy(mut self, policy: Policy<'a>) -> Transcriber<'a> {
+        self.policy = policy;
+        self
+    }
+
+    /// Splits text into words and chooses a pronunciation for each of them
+    ///
+    /// Tokens that can't be pronounced at all, like a lone `#`, are left out, as are the
+    /// symbols between the numbers of a token like "5:30".
+    pub fn transcribe(&self, text: &str) -> Transcription {
+        let tokens: Vec<Token> = pieces(text)
+            .into_iter()
+            .map(|(offset, piece)| self.split(offset, piece))
+            .collect();
+        let capitalized: Vec<&str> = tokens
+            .iter()
+            .map(|t| t.text)
+            .filter(|t| t.chars().filter(|c| c.is_alphabetic()).count() > 1)
+            .collect();
+        let shouting =
+            capitalized.len() > 2 && capitalized.iter().all(|t| !t.chars().any(char::is_lowercase));
+        let mut words: Vec<Word> = Vec::new();
+        for (idx, token) in tokens.iter().enumerate() {
+            // an opening bracket ends the phrase before it
+            mark(&mut words, boundary(token.leading));
+            if !token.text.is_empty() {
+                let previous = tokens[..idx].iter().rev().map(|t| t.text).find(|t| !t.is_empty());
+                let next = tokens[idx + 1..].iter().map(|t| t.text).find(|t| !t.is_empty());
+                let context = Context {
+                    previous,
+                    next,
+                    shouting,
+                };
+                self.token(token.text, token.offset, context, &mut words);
+            }
+            mark(&mut words, boundary(token.trailing));
+        }
+        if let Some(last) = words.last_mut() {
+            last.boundary = Boundary::Sentence;
+        }
+        Transcription { words }
+    }
+
+    // separates a piece of text from the punctuation around it
+    fn split<'t>(&self, offset: usize, piece: &'t str) -> Token<'t> {
+        let start = piece
+            .char_indices()
+            .find(|&(idx, c)| starts_token(c, &piece[idx..]));
+        let start = match start {
+            Some((start, _)) => start,
+            None => {
+                return Token {
+                    offset,
+                    leading: piece,
+                    text: "",
+                    trailing: "",
+                }
+            }
+        };
+        let end = piece
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| ends_token(c))
+            .map(|(idx, c)| idx + c.len_utf8())
+            .unwrap_or(piece.len())
+            .max(start);
+        let mut text = &piece[start..end];
+        let mut trailing = &piece[end..];
+        // the period of an abbreviation is part of it, and doesn't end the sentence
+        let period = trailing.starts_with('.') && !trailing.starts_with("..");
+        if period && self.keeps_period(text) {
+            text = &piece[start..=end];
+            trailing = &trailing[1..];
+        }
+        Token {
+            offset: offset + start,
+            leading: &piece[..start],
+            text,
+            trailing,
+        }
+    }
+
+    fn keeps_period(&self, token: &str) -> bool {
+        let lower = normalize(token);
+        if ABBREVIATIONS.iter().any(|&(short, _)| short == lower) {
+            return true;
+        }
+        // initials and dotted abbreviations like "u.s.", but not words like "in." that only
+        // have an entry with a period because they are also abbreviations
+        let dotted = lower.chars().count() == 1 || lower.contains('.');
+        let initials = lower.contains('.') && acronym(token).is_some();
+        initials || (dotted && self.dict.get(&format!("{}.", lower)).is_some())
+    }
+
+    fn token(&self, token: &str, offset: usize, context: Context, words: &mut Vec<Word>) {
+        let span = offset..offset + token.len();
+        let lower = normalize(token);
+        if let Some(&(_, expansion)) = ABBREVIATIONS
+            .iter()
+            .find(|&&(short, _)| short == lower.trim_end_matches('.'))
+        {
+            for word in expansion.split(' ') {
+                self.push(word, span.clone(), words);
+            }
+            return;
+        }
+        if let Some((acronym, plural)) = acronym(token).filter(|_| !context.shouting) {
+            let empty = Acronyms::new();
+            let acronyms = self.acronyms.unwrap_or(&empty);
+            if let Some(lookup) = acronyms.pronounce(self.dict, acronym, self.predictor) {
+                let mut rule = self.choose(acronym, lookup.rules());
+                let mut source = lookup.source();
+                if plural {
+                    let mut phones = rule.pronunciation().to_vec();
+                    phones.extend(phones.last().map(|&last| s_suffix(last)).unwrap_or_default());
+                    rule = Rule::new(lower.clone(), phones);
+                    source = Source::Derived;
+                }
+                words.push(word(&lower, span, rule, source));
+                return;
+            }
+        }
+        if let Some(rules) = self.dict.get(&lower) {
+            words.push(word(&lower, span, self.choose(&lower, rules), Source::Dictionary));
+            return;
+        }
+        let hint = self.hints.hint(token, context.previous, context.next);
+        if let Some(spoken) = verbalize(&lower, hint) {
+            for word in spoken {
+                self.push(word, span.clone(), words);
+            }
+            return;
+        }
+        let mut chars = lower.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if let Some(&(_, name)) = SYMBOLS.iter().find(|&&(symbol, _)| symbol == c) {
+                self.push(name, span, words);
+                return;
+            }
+        }
+
+        // hyphenated compounds and slashed alternatives are read part by part
+        if token.contains(['-', '/']) {
+            let mut start = 0;
+            for part in token.split(['-', '/']) {
+                if !part.is_empty() {
+                    self.token(part, offset + start, context, words);
+                }
+                start += part.len() + 1;
+            }
+            return;
+        }
+        // so are the runs of digits of a token that isn't a number, like "mp3" or "5:30", and
+        // what is between them
+        if lower.chars().any(|c| c.is_ascii_digit()) {
+            let mut start = 0;
+            for (idx, c) in token.char_indices().skip(1) {
+                let previous = token[..idx].chars().next_back().map(|p| p.is_ascii_digit());
+                if previous != Some(c.is_ascii_digit()) {
+                    self.token(&token[start..idx], offset + start, context, words);
+                    start = idx;
+                }
+            }
+            self.token(&token[start..], offset + start, context, words);
+            return;
+        }
+
+        if let Some(phones) = self.contraction(&lower) {
+            let rule = Rule::new(lower.clone(), phones);
+            words.push(word(&lower, span, rule, Source::Derived));
+            return;
+        }
+        if let Some(rule) = self.dict.derive(&lower).into_iter().next() {
+            words.push(word(&lower, span, rule, Source::Derived));
+            return;
+        }
+        if let Some(compound) = self.dict.decompose(&lower).into_iter().next() {
+            words.push(word(&lower, span, compound.into_rule(), Source::Derived));
+            return;
+        }
+        let phones = self.predictor.predict(&lower);
+        if !phones.is_empty() {
+            let rule = Rule::new(lower.clone(), phones);
+            words.push(word(&lower, span, rule, Source::Predicted));
+        }
+    }
+
+    // looks up a word that a token was expanded to
+    fn push(&self, spoken: &str, span: Range<usize>, words: &mut Vec<Word>) {
+        let lookup = self.dict.get_or_predict(spoken, self.predictor);
+        words.push(word(spoken, span, self.choose(spoken, lookup.rules()), lookup.source()));
+    }
+
+    // one of the pronunciations of a word that was found, chosen with the policy
+    fn choose(&self, spoken: &str, rules: &[Rule]) -> Rule {
+        self.policy.select(spoken, rules).unwrap_or(&rules[0]).clone()
+    }
+
+    // builds the pronunciation of a stem in the dictionary followed by a clitic like "'ll",
+    // leaving "'s" to `Cmudict::derive`
+    fn contraction(&self, word: &str) -> Option<Vec<Symbol>> {
+        let idx = word.rfind('\'')?;
+        let (mut stem, clitic) = word.split_at(idx);
+        let consonant = match clitic {
+            "'t" if stem.ends_with('n') => {
+                stem = &stem[..stem.len() - 1];
+                vec![Symbol::N, Symbol::T]
+            }
+            "'ve" => vec![Symbol::V],
+            "'ll" => vec![Symbol::L],
+            "'d" => vec![Symbol::D],
+            "'m" => vec![Symbol::M],
+            "'re" => vec![Symbol::R],
+            _ => return None,
+        };
+        let mut phones = self.dict.get(stem)?.first()?.pronunciation().to_vec();
+        let last = *phones.last()?;
+        // a vowel is slipped in after a consonant, except for "'re", which becomes a syllabic
+        // r
+        if consonant == [Symbol::R] && !last.is_syllable() {
+            phones.push(Symbol::ER(Stress::None));
+        } else {
+            if !last.is_syllable() {
+                phones.push(Symbol::AH(Stress::None));
+            }
+            phones.extend(consonant);
+        }
+        Some(phones)
+    }
+}
+
+impl Cmudict {
+    /// Splits text into words and chooses a pronunciation for each of them, guessing the
+    /// pronunciations of words that aren't in the dictionary with `predictor`
+    ///
+    /// This is a shorthand for `Transcriber::new(self, predictor).transcribe(text)`.
+    pub fn transcribe<P>(&self, text: &str, predictor: &P) -> Transcription
+    where
+        P: Predictor,
+    {
+        Transcriber::new(self, predictor).transcribe(text)
+    }
+}
+
+// an acronym written in capitals, like "FBI" or "F.B.I.", and whether it ends in a plural or
+// possessive "s"
+fn acronym(token: &str) -> Option<(&str, bool)> {
+    let token = token.trim_end_matches('.');
+    let (stem, plural) = match token
+        .strip_suffix("'s")
+        .or_else(|| token.strip_suffix("\u{2019}s"))
+        .or_else(|| token.strip_suffix('s'))
+    {
+        Some(stem) => (stem, true),
+        None => (token, false),
+    };
+    let letters = stem.chars().filter(|&c| c != '.').count();
+    let capitals = stem.chars().all(|c| c.is_ascii_uppercase() || c == '.');
+    let dotted = !stem.contains('.') || stem.split('.').all(|part| part.chars().count() == 1);
+    if letters > 1 && capitals && dotted {
+        Some((stem, plural))
+    } else {
+        None
+    }
+}
+
+fn word(text: &str, span: Range<usize>, rule: Rule, source: Source) -> Word {
+    Word {
+        text: text.to_string(),
+        span,
+        rule,
+        source,
+        boundary: Boundary::Word,
+    }
+}
+
+fn normalize(token: &str) -> String {
+    token.to_lowercase().replace(['\u{2019}', '\u{2018}'], "'")
+}
+
+// splits text on whitespace and dashes, along with the byte offset of each piece
+fn pieces(text: &str) -> Vec<(usize, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let double = c == '-' && matches!(chars.peek(), Some(&(_, '-')));
+        let dash = c == '\u{2014}' || c == '\u{2013}' || double;
+        if c.is_whitespace() || dash {
+            if let Some(start) = start.take() {
+                pieces.push((start, &text[start..idx]));
+            }
+            // a dash is a piece of its own, so it can end a phrase
+            if dash {
+                if double {
+                    chars.next();
+                }
+                let end = if double { idx + 2 } else { idx + c.len_utf8() };
+                pieces.push((idx, &text[idx..end]));
+            }
+        } else if start.is_none() {
+            start = Some(idx);
+        }
+    }
+    if let Some(start) = start {
+        pieces.push((start, &text[start..]));
+    }
+    pieces
+}
+
+fn starts_token(c: char, rest: &str) -> bool {
+    let digit_follows = || rest[c.len_utf8()..].starts_with(|n: char| n.is_ascii_digit());
+    c.is_alphanumeric()
+        || SYMBOLS.iter().any(|&(symbol, _)| symbol == c)
+        || ((c == '-' || c == '.' || CURRENCIES.contains(c)) && digit_follows())
+}
+
+fn ends_token(c: char) -> bool {
+    c.is_alphanumeric() || SYMBOLS.iter().any(|&(symbol, _)| symbol == c)
+}
+
+// how strong a boundary the punctuation between two words makes
+fn boundary(punctuation: &str) -> Boundary {
+    let ellipsis = punctuation.contains("..") || punctuation.contains('\u{2026}');
+    if punctuation.contains(['!', '?']) || (punctuation.contains('.') && !ellipsis) {
+        Boundary::Sentence
+    } else if ellipsis || punctuation.contains(|c| ",;:()[]{}-\u{2013}\u{2014}".contains(c)) {
+        Boundary::Phrase
+    } else {
+        Boundary::Word
+    }
+}
+
+fn mark(words: &mut [Word], boundary: Boundary) {
+    if let Some(last) = words.last_mut() {
+        last.boundary = last.boundary.max(boundary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::g2p::LetterRules;
+    use crate::acronym::Reading;
+    use crate::normalize::Hint;
+    use crate::core::Symbol::*;
+
+    fn texts(transcription: &Transcription) -> Vec<&str> {
+        transcription.words().iter().map(|w| w.text()).collect()
+    }
+
+    #[test]
+    fn transcribing() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let t = Transcriber::new(&d, &rules);
+
+        let text = "Hello, world! It's 5 o'clock -- isn't it?";
+        let hello = t.transcribe(text);
+        assert_eq!(
+            texts(&hello),
+            vec!["hello", "world", "it's", "five", "o'clock", "isn't", "it"]
+        );
+        let boundaries: Vec<Boundary> = hello.words().iter().map(|w| w.boundary()).collect();
+        use Boundary::*;
+        assert_eq!(boundaries, vec![Phrase, Sentence, Word, Word, Phrase, Word, Sentence]);
+        assert!(hello.words().iter().all(|w| w.source() == Source::Dictionary));
+        assert_eq!(&text[hello.words()[1].span()], "world");
+        assert_eq!(
+            hello.symbols().len(),
+            hello.words().iter().map(|w| w.pronunciation().len()).sum::<usize>()
+        );
+
+        // an abbreviation's period doesn't end the sentence, and initials are letters
+        let mr = t.transcribe("Mr. J. Smith vs. the U.S.");
+        assert_eq!(texts(&mr), vec!["mister", "j.", "smith", "versus", "the", "u.s."]);
+        assert!(mr.words()[..5].iter().all(|w| w.boundary() == Word));
+
+        // hyphens and digits split words, and unknown words are guessed
+        let mixed = t.transcribe("the mp3 blue-green zorbled");
+        assert_eq!(texts(&mixed), vec!["the", "mp", "three", "blue", "green", "zorbled"]);
+        assert_eq!(mixed.words()[5].source(), Source::Predicted);
+        assert_eq!(&"the mp3 blue-green zorbled"[mixed.words()[4].span()], "green");
+
+        // compounds of words in the dictionary are read as their parts
+        let seed = t.transcribe("a sunflowerseed");
+        assert_eq!(seed.words()[1].source(), Source::Derived);
+        let compound = d.decompose("sunflowerseed");
+        assert_eq!(seed.words()[1].pronunciation(), compound[0].pronunciation());
+
+        // the first pronunciation is taken unless a policy says otherwise
+        let tomato = d.get("tomato").unwrap();
+        assert_eq!(t.transcribe("tomato").words()[0].pronunciation(), tomato[0].pronunciation());
+        let aa = Policy::Prefer(vec![Symbol::AA(Stress::None)]);
+        let merged = Transcriber::new(&d, &rules).with_policy(aa).transcribe("tomato");
+        assert_eq!(merged.words()[0].pronunciation(), tomato[1].pronunciation());
+
+        assert!(t.transcribe("").words().is_empty());
+        assert!(t.transcribe(" ... ").words().is_empty());
+    }
+
+    #[test]
+    fn numbers() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let t = Transcriber::new(&d, &rules);
+
+        let call = t.transcribe("Call 911 about the $3.50 in 1999.");
+        assert_eq!(
+            texts(&call),
+            vec![
+                "call", "nine", "one", "one", "about", "the", "three", "dollars", "and", "fifty",
+                "cents", "in", "nineteen", "ninety", "nine"
+            ]
+        );
+        assert!(call.words().iter().all(|w| w.source() == Source::Dictionary));
+
+        // the numbers of tokens that aren't numbers are read one by one
+        let read = |text: &str| texts(&t.transcribe(text)).join(" ");
+        assert_eq!(read("at 5:30"), "at five thirty");
+        assert_eq!(read("version 2.0.1"), "version two zero one");
+        assert_eq!(read("5'10\""), "five ten");
+        assert_eq!(read("2^8"), "two eight");
+        assert_eq!(read("1,2,3"), "one two three");
+
+        let counts = |_: &str, _: Option<&str>, _: Option<&str>| Some(Hint::Cardinal);
+        let t = t.with_hints(&counts);
+        assert_eq!(
+            texts(&t.transcribe("1999")),
+            vec!["one", "thousand", "nine", "hundred", "ninety", "nine"]
+        );
+    }
+
+    #[test]
+    fn acronyms() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let t = Transcriber::new(&d, &rules);
+
+        let text = "The FBI and NASA sent CDs to the U.S.A. today";
+        let sent = t.transcribe(text);
+        assert_eq!(
+            texts(&sent),
+            vec!["the", "fbi", "and", "nasa", "sent", "cds", "to", "the", "u.s.a.", "today"]
+        );
+        assert_eq!(sent.words()[1].pronunciation(), &d.spell_out("fbi").unwrap()[..]);
+        assert_eq!(sent.words()[3].source(), Source::Dictionary);
+        assert_eq!(sent.words()[5].source(), Source::Derived);
+        assert_eq!(sent.words()[5].pronunciation().last(), Some(&Z));
+        // the periods of the initials don't end the sentence
+        assert_eq!(sent.words()[8].boundary(), Boundary::Word);
+        assert_eq!(&text[sent.words()[8].span()], "U.S.A.");
+
+        // shouted words aren't acronyms
+        let shouted = t.transcribe("GET OUT OF HERE");
+        assert_eq!(shouted.words()[0].pronunciation(), d.get("get").unwrap()[0].pronunciation());
+
+        let list: Acronyms = vec![("NASA", Reading::Letters)].into_iter().collect();
+        let t = t.with_acronyms(&list);
+        let nasa = t.transcribe("NASA");
+        assert_eq!(nasa.words()[0].pronunciation(), &d.spell_out("nasa").unwrap()[..]);
+    }
+
+    #[test]
+    fn contractions() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        let t = Transcriber::new(&d, &rules);
+
+        let derived = |word: &str| {
+            let transcription = t.transcribe(word);
+            let word = &transcription.words()[0];
+            assert_eq!(word.source(), Source::Derived, "{}", word.text());
+            word.pronunciation().to_vec()
+        };
+        let p = |word: &str| d.get(word).unwrap()[0].pronunciation().to_vec();
+        assert_eq!(derived("wombat's"), [&p("wombat")[..], &[S]].concat());
+        assert_eq!(derived("judo\u{2019}s"), [&p("judo")[..], &[Z]].concat());
+        assert_eq!(derived("walrus's"), [&p("walrus")[..], &[IH(Stress::None), Z]].concat());
+        assert_eq!(derived("hedgehog'll"), [&p("hedgehog")[..], &[AH(Stress::None), L]].concat());
+        assert_eq!(derived("judo'll"), [&p("judo")[..], &[L]].concat());
+    }
+
+    #[test]
+    fn display() {
+        let d: Cmudict = "cat K AE1 T\nsat S AE1 T".parse().expect("Could not create Cmudict");
+        let rules = LetterRules::english();
+        assert_eq!(d.transcribe("Cat sat.", &rules).to_string(), "K AE1 T | S AE1 T");
+    }
+}
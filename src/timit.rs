@@ -0,0 +1,698 @@
+//! The extended phone set of the TIMIT and Buckeye corpora
+//!
+//! TIMIT transcribes speech with 61 phones, which add to the 39 of the CMU dictionary the
+//! closures before stops, flaps, glottal stops, syllabic consonants, reduced vowels and
+//! pauses. When scoring recognisers these are usually folded down to 48 and then 39 classes
+//! following Lee & Hon (1989), and the same folds are available here. Buckeye uses nearly the
+//! same labels, and its glottal stop `tq` and nasalised vowels such as `ahn` are read as the
+//! closest TIMIT phone.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{ParseError, ParseResult};
+
+/// A phone from TIMIT's set of 61
+///
+/// Vowels carry a stress like they do in `Symbol`. TIMIT's own transcriptions don't mark
+/// stress so their vowels are all `Stress::None`, but pronouncing dictionaries such as
+/// TIMITDIC do.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum Phone {
+    AA(Stress),
+    AE(Stress),
+    AH(Stress),
+    AO(Stress),
+    AW(Stress),
+    /// `ax`, the schwa
+    AX(Stress),
+    /// `ax-h`, a devoiced schwa
+    AXH(Stress),
+    /// `axr`, the r-coloured schwa
+    AXR(Stress),
+    AY(Stress),
+    EH(Stress),
+    ER(Stress),
+    EY(Stress),
+    IH(Stress),
+    /// `ix`, a reduced high vowel
+    IX(Stress),
+    IY(Stress),
+    OW(Stress),
+    OY(Stress),
+    UH(Stress),
+    UW(Stress),
+    /// `ux`, a fronted `uw`
+    UX(Stress),
+    B,
+    /// `bcl`, the closure before `b`
+    BCL,
+    CH,
+    D,
+    /// `dcl`, the closure before `d`
+    DCL,
+    DH,
+    /// `dx`, the flap in "butter"
+    DX,
+    /// `el`, a syllabic l
+    EL,
+    /// `em`, a syllabic m
+    EM,
+    /// `en`, a syllabic n
+    EN,
+    /// `eng`, a syllabic ng
+    ENG,
+    /// `epi`, an epenthetic silence
+    EPI,
+    F,
+    G,
+    /// `gcl`, the closure before `g`
+    GCL,
+    /// `h#`, the silence at the start and end of an utterance
+    HASH,
+    HH,
+    /// `hv`, a voiced h
+    HV,
+    JH,
+    K,
+    /// `kcl`, the closure before `k`
+    KCL,
+    L,
+    M,
+    N,
+    NG,
+    /// `nx`, the nasal flap in "winner"
+    NX,
+    P,
+    /// `pau`, a pause
+    PAU,
+    /// `pcl`, the closure before `p`
+    PCL,
+    /// `q`, a glottal stop
+    Q,
+    R,
+    S,
+    SH,
+    T,
+    /// `tcl`, the closure before `t`
+    TCL,
+    TH,
+    V,
+    W,
+    Y,
+    Z,
+    ZH,
+}
+
+impl Phone {
+    /// Returns the phone's TIMIT label, without any stress
+    pub fn label(&self) -> &'static str {
+        use self::Phone::*;
+        match *self {
+            AA(_) => "aa",
+            AE(_) => "ae",
+            AH(_) => "ah",
+            AO(_) => "ao",
+            AW(_) => "aw",
+            AX(_) => "ax",
+            AXH(_) => "ax-h",
+            AXR(_) => "axr",
+            AY(_) => "ay",
+            EH(_) => "eh",
+            ER(_) => "er",
+            EY(_) => "ey",
+            IH(_) => "ih",
+            IX(_) => "ix",
+            IY(_) => "iy",
+            OW(_) => "ow",
+            OY(_) => "oy",
+            UH(_) => "uh",
+            UW(_) => "uw",
+            UX(_) => "ux",
+            B => "b",
+            BCL => "bcl",
+            CH => "ch",
+            D => "d",
+            DCL => "dcl",
+            DH => "dh",
+            DX => "dx",
+            EL => "el",
+            EM => "em",
+            EN => "en",
+            ENG => "eng",
+            EPI => "epi",
+            F => "f",
+            G => "g",
+            GCL => "gcl",
+            HASH => "h#",
+            HH => "hh",
+            HV => "hv",
+            JH => "jh",
+            K => "k",
+            KCL => "kcl",
+            L => "l",
+            M => "m",
+            N => "n",
+            NG => "ng",
+            NX => "nx",
+            P => "p",
+            PAU => "pau",
+            PCL => "pcl",
+            Q => "q",
+            R => "r",
+            S => "s",
+            SH => "sh",
+            T => "t",
+            TCL => "tcl",
+            TH => "th",
+            V => "v",
+            W => "w",
+            Y => "y",
+            Z => "z",
+            ZH => "zh",
+        }
+    }
+
+    /// Returns the stress of a vowel, or `None` for anything else
+    pub fn stress(&self) -> Option<Stress> {
+        use self::Phone::*;
+        match *self {
+            AA(s) | AE(s) | AH(s) | AO(s) | AW(s) | AX(s) | AXH(s) | AXR(s) | AY(s) | EH(s)
+            | ER(s) | EY(s) | IH(s) | IX(s) | IY(s) | OW(s) | OY(s) | UH(s) | UW(s) | UX(s) => {
+                Some(s)
+            }
+            _ => None,
+        }
+    }
+
+    // the same phone with another stress, if it's a vowel
+    pub(crate) fn with_stress(&self, stress: Stress) -> Phone {
+        use self::Phone::*;
+        match *self {
+            AA(_) => AA(stress),
+            AE(_) => AE(stress),
+            AH(_) => AH(stress),
+            AO(_) => AO(stress),
+            AW(_) => AW(stress),
+            AX(_) => AX(stress),
+            AXH(_) => AXH(stress),
+            AXR(_) => AXR(stress),
+            AY(_) => AY(stress),
+            EH(_) => EH(stress),
+            ER(_) => ER(stress),
+            EY(_) => EY(stress),
+            IH(_) => IH(stress),
+            IX(_) => IX(stress),
+            IY(_) => IY(stress),
+            OW(_) => OW(stress),
+            OY(_) => OY(stress),
+            UH(_) => UH(stress),
+            UW(_) => UW(stress),
+            UX(_) => UX(stress),
+            phone => phone,
+        }
+    }
+
+    /// Returns `true` if the phone is a vowel
+    pub fn is_vowel(&self) -> bool {
+        self.stress().is_some()
+    }
+
+    /// Returns `true` if the phone can make a syllable on its own, which includes the syllabic
+    /// consonants `el`, `em`, `en` and `eng` as well as the vowels
+    pub fn is_syllable(&self) -> bool {
+        self.is_vowel() || matches!(self, Phone::EL | Phone::EM | Phone::EN | Phone::ENG)
+    }
+
+    /// Returns `true` for the closures `bcl`, `dcl`, `gcl`, `pcl`, `tcl` and `kcl`
+    pub fn is_closure(&self) -> bool {
+        self.release().is_some()
+    }
+
+    /// Returns `true` for the silences `pau`, `epi` and `h#`
+    pub fn is_silence(&self) -> bool {
+        matches!(self, Phone::PAU | Phone::EPI | Phone::HASH)
+    }
+
+    // returns `true` if the phone is the closure before `next`, a stop or an affricate
+    fn closes(&self, next: &Phone) -> bool {
+        use self::Phone::*;
+        matches!((self, next), (TCL, CH) | (DCL, JH)) || self.release() == Some(*next)
+    }
+
+    // the stop released after a closure
+    fn release(&self) -> Option<Phone> {
+        use self::Phone::*;
+        match *self {
+            BCL => Some(B),
+            DCL => Some(D),
+            GCL => Some(G),
+            PCL => Some(P),
+            TCL => Some(T),
+            KCL => Some(K),
+            _ => None,
+        }
+    }
+
+    /// Folds the phone into the 48 classes of Lee & Hon
+    ///
+    /// The voiceless closures become `cl`, the voiced ones `vcl`, and `pau` and `h#` become
+    /// `sil`. Glottal stops are dropped, so `q` gives `None`.
+    pub fn fold_48(&self) -> Option<&'static str> {
+        use self::Phone::*;
+        let class = match *self {
+            Q => return None,
+            UX(_) => "uw",
+            AXR(_) => "er",
+            AXH(_) => "ax",
+            EM => "m",
+            NX => "n",
+            ENG => "ng",
+            HV => "hh",
+            PCL | TCL | KCL => "cl",
+            BCL | DCL | GCL => "vcl",
+            PAU | HASH => "sil",
+            _ => self.label(),
+        };
+        Some(class)
+    }
+
+    /// Folds the phone into the 39 classes of Lee & Hon
+    ///
+    /// On top of the folds to 48 classes, every closure and silence becomes `sil`, and `el`,
+    /// `en`, `ix`, `ax`, `ao` and `zh` are merged with `l`, `n`, `ih`, `ah`, `aa` and `sh`.
+    pub fn fold_39(&self) -> Option<&'static str> {
+        let class = match self.fold_48()? {
+            "cl" | "vcl" | "epi" => "sil",
+            "el" => "l",
+            "en" => "n",
+            "ix" => "ih",
+            "ax" => "ah",
+            "ao" => "aa",
+            "zh" => "sh",
+            class => class,
+        };
+        Some(class)
+    }
+
+    /// Converts the phone into the symbols the CMU dictionary would use for it
+    ///
+    /// Reduced vowels become their full counterparts, so `ax` is `AH` and `ix` is `IH`, and
+    /// syllabic consonants get a schwa in front of them. Flaps become the `T` or `N` they
+    /// usually stand for, and a closure becomes its stop. Silences and glottal stops have no
+    /// symbols at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmudict_fast as cmudict;
+    /// use cmudict::{Phone, Symbol, Stress};
+    ///
+    /// assert_eq!(Phone::DX.to_symbols(), vec![Symbol::T]);
+    /// assert_eq!(Phone::EL.to_symbols(), vec![Symbol::AH(Stress::None), Symbol::L]);
+    /// assert_eq!(Phone::PAU.to_symbols(), vec![]);
+    /// ```
+    pub fn to_symbols(&self) -> Vec<Symbol> {
+        use self::Phone::*;
+        let symbol = match *self {
+            AA(s) => Symbol::AA(s),
+            AE(s) => Symbol::AE(s),
+            AH(s) | AX(s) | AXH(s) => Symbol::AH(s),
+            AO(s) => Symbol::AO(s),
+            AW(s) => Symbol::AW(s),
+            AXR(s) | ER(s) => Symbol::ER(s),
+            AY(s) => Symbol::AY(s),
+            EH(s) => Symbol::EH(s),
+            EY(s) => Symbol::EY(s),
+            IH(s) | IX(s) => Symbol::IH(s),
+            IY(s) => Symbol::IY(s),
+            OW(s) => Symbol::OW(s),
+            OY(s) => Symbol::OY(s),
+            UH(s) => Symbol::UH(s),
+            UW(s) | UX(s) => Symbol::UW(s),
+            B | BCL => Symbol::B,
+            CH => Symbol::CH,
+            D | DCL => Symbol::D,
+            DH => Symbol::DH,
+            DX | T | TCL => Symbol::T,
+            EL => return vec![Symbol::AH(Stress::None), Symbol::L],
+            EM => return vec![Symbol::AH(Stress::None), Symbol::M],
+            EN => return vec![Symbol::AH(Stress::None), Symbol::N],
+            ENG => return vec![Symbol::AH(Stress::None), Symbol::NG],
+            EPI | HASH | PAU | Q => return Vec::new(),
+            F => Symbol::F,
+            G | GCL => Symbol::G,
+            HH | HV => Symbol::HH,
+            JH => Symbol::JH,
+            K | KCL => Symbol::K,
+            L => Symbol::L,
+            M => Symbol::M,
+            N | NX => Symbol::N,
+            NG => Symbol::NG,
+            P | PCL => Symbol::P,
+            R => Symbol::R,
+            S => Symbol::S,
+            SH => Symbol::SH,
+            TH => Symbol::TH,
+            V => Symbol::V,
+            W => Symbol::W,
+            Y => Symbol::Y,
+            Z => Symbol::Z,
+            ZH => Symbol::ZH,
+        };
+        vec![symbol]
+    }
+}
+
+impl From<Symbol> for Phone {
+    fn from(symbol: Symbol) -> Phone {
+        use crate::core::Symbol::*;
+        match symbol {
+            AA(s) => Phone::AA(s),
+            AE(s) => Phone::AE(s),
+            AH(s) => Phone::AH(s),
+            AO(s) => Phone::AO(s),
+            AW(s) => Phone::AW(s),
+            AY(s) => Phone::AY(s),
+            B => Phone::B,
+            CH => Phone::CH,
+            D => Phone::D,
+            DH => Phone::DH,
+            EH(s) => Phone::EH(s),
+            ER(s) => Phone::ER(s),
+            EY(s) => Phone::EY(s),
+            F => Phone::F,
+            G => Phone::G,
+            HH => Phone::HH,
+            IH(s) => Phone::IH(s),
+            IY(s) => Phone::IY(s),
+            JH => Phone::JH,
+            K => Phone::K,
+            L => Phone::L,
+            M => Phone::M,
+            N => Phone::N,
+            NG => Phone::NG,
+            OW(s) => Phone::OW(s),
+            OY(s) => Phone::OY(s),
+            P => Phone::P,
+            R => Phone::R,
+            S => Phone::S,
+            SH => Phone::SH,
+            T => Phone::T,
+            TH => Phone::TH,
+            UH(s) => Phone::UH(s),
+            UW(s) => Phone::UW(s),
+            V => Phone::V,
+            W => Phone::W,
+            Y => Phone::Y,
+            Z => Phone::Z,
+            ZH => Phone::ZH,
+        }
+    }
+}
+
+impl fmt::Display for Phone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.stress() {
+            Some(Stress::None) | None => write!(f, "{}", self.label()),
+            Some(stress) => write!(f, "{}{}", self.label(), stress),
+        }
+    }
+}
+
+impl FromStr for Phone {
+    type Err = ParseError;
+
+    /// Reads a TIMIT or Buckeye label in either case, with an optional stress digit on vowels
+    fn from_str(s: &str) -> ParseResult<Phone> {
+        use self::Phone::*;
+        let lower = s.to_ascii_lowercase();
+        let (label, stress) = match lower.as_bytes().last() {
+            Some(b'0') => (&lower[..lower.len() - 1], Some(Stress::None)),
+            Some(b'1') => (&lower[..lower.len() - 1], Some(Stress::Primary)),
+            Some(b'2') => (&lower[..lower.len() - 1], Some(Stress::Secondary)),
+            _ => (&lower[..], None),
+        };
+        let vowel = |s: &str, stress: Stress| -> Option<Phone> {
+            let phone = match s {
+                "aa" => AA(stress),
+                "ae" => AE(stress),
+                "ah" => AH(stress),
+                "ao" => AO(stress),
+                "aw" => AW(stress),
+                "ax" => AX(stress),
+                "ax-h" => AXH(stress),
+                "axr" => AXR(stress),
+                "ay" => AY(stress),
+                "eh" => EH(stress),
+                "er" => ER(stress),
+                "ey" => EY(stress),
+                "ih" => IH(stress),
+                "ix" => IX(stress),
+                "iy" => IY(stress),
+                "ow" => OW(stress),
+                "oy" => OY(stress),
+                "uh" => UH(stress),
+                "uw" => UW(stress),
+                "ux" => UX(stress),
+                _ => return None,
+            };
+            Some(phone)
+        };
+        let unknown = || ParseError::UnknownPhone(s.to_string());
+        if let Some(phone) = vowel(label, stress.unwrap_or(Stress::None)) {
+            return Ok(phone);
+        }
+        if stress.is_some() {
+            // Buckeye marks nasalised vowels with a trailing n
+            return label
+                .strip_suffix('n')
+                .and_then(|base| vowel(base, stress.unwrap_or(Stress::None)))
+                .ok_or_else(unknown);
+        }
+        let phone = match label {
+            "b" => B,
+            "bcl" => BCL,
+            "ch" => CH,
+            "d" => D,
+            "dcl" => DCL,
+            "dh" => DH,
+            "dx" => DX,
+            "el" => EL,
+            "em" => EM,
+            "en" => EN,
+            "eng" => ENG,
+            "epi" => EPI,
+            "f" => F,
+            "g" => G,
+            "gcl" => GCL,
+            "h#" => HASH,
+            "hh" => HH,
+            "hv" => HV,
+            "jh" => JH,
+            "k" => K,
+            "kcl" => KCL,
+            "l" => L,
+            "m" => M,
+            "n" => N,
+            "ng" => NG,
+            "nx" => NX,
+            "p" => P,
+            "pau" => PAU,
+            "pcl" => PCL,
+            "q" | "tq" => Q,
+            "r" => R,
+            "s" => S,
+            "sh" => SH,
+            "t" => T,
+            "tcl" => TCL,
+            "th" => TH,
+            "v" => V,
+            "w" => W,
+            "y" => Y,
+            "z" => Z,
+            "zh" => ZH,
+            _ => {
+                return label
+                    .strip_suffix('n')
+                    .and_then(|base| vowel(base, Stress::None))
+                    .ok_or_else(unknown)
+            }
+        };
+        Ok(phone)
+    }
+}
+
+/// Converts a TIMIT transcription into the symbols the CMU dictionary would use for it
+///
+/// Each phone becomes the symbols given by `Phone::to_symbols`, except that a closure
+/// followed by its own release is only counted once, as are the closures of the affricates,
+/// `tcl ch` and `dcl jh`.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{collapse, Phone, Symbol, Stress};
+///
+/// // "butter", with a flap
+/// let phones: Vec<Phone> = "h# bcl b ah1 dx axr h#"
+///     .split(' ')
+///     .map(|p| p.parse().unwrap())
+///     .collect();
+/// assert_eq!(
+///     collapse(&phones),
+///     vec![Symbol::B, Symbol::AH(Stress::Primary), Symbol::T, Symbol::ER(Stress::None)]
+/// );
+/// ```
+pub fn collapse(phones: &[Phone]) -> Vec<Symbol> {
+    phones
+        .iter()
+        .enumerate()
+        .filter(|&(idx, phone)| !phones.get(idx + 1).is_some_and(|next| phone.closes(next)))
+        .flat_map(|(_, phone)| phone.to_symbols())
+        .collect()
+}
+
+/// The pronunciation of a word written with TIMIT phones
+///
+/// Lines are read in the form used by both the CMU dictionary and TIMITDIC, a label followed
+/// by its phones, which may be wrapped in slashes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtendedRule {
+    label: String,
+    pronunciation: Vec<Phone>,
+}
+
+impl ExtendedRule {
+    /// Returns the word
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the phones of the word
+    pub fn pronunciation(&self) -> &[Phone] {
+        &self.pronunciation
+    }
+
+    /// Converts the pronunciation into CMU symbols with `collapse`
+    pub fn to_rule(&self) -> Rule {
+        Rule::new(self.label.clone(), collapse(&self.pronunciation))
+    }
+}
+
+impl FromStr for ExtendedRule {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<ExtendedRule> {
+        let mut iter = s.split_whitespace();
+        let label = iter.next().ok_or(ParseError::UnexpectedEOF("label"))?;
+        let pronunciation = iter
+            .map(|p| p.trim_matches('/'))
+            .filter(|p| !p.is_empty())
+            .map(Phone::from_str)
+            .collect::<ParseResult<Vec<_>>>()?;
+        Ok(ExtendedRule {
+            label: label.to_string(),
+            pronunciation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    const TIMIT: &str = "iy ih eh ey ae aa aw ay ah ao oy ow uh uw ux er ax ix axr ax-h \
+        jh ch b d g p t k dx s sh z zh f th v dh m n ng em nx en eng l r w y hh hv el \
+        bcl dcl gcl pcl tcl kcl q pau epi h#";
+
+    fn phones(s: &str) -> Vec<Phone> {
+        s.split_whitespace().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parsing() {
+        let all = phones(TIMIT);
+        assert_eq!(all.len(), 61);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), 61);
+        for phone in &all {
+            assert_eq!(&phone.to_string().parse::<Phone>().unwrap(), phone);
+        }
+
+        assert_eq!("AE1".parse(), Ok(Phone::AE(Stress::Primary)));
+        assert_eq!("EL".parse(), Ok(Phone::EL));
+        assert_eq!("tq".parse(), Ok(Phone::Q));
+        assert_eq!("ahn".parse(), Ok(Phone::AH(Stress::None)));
+        assert_eq!("en".parse(), Ok(Phone::EN));
+        assert_eq!(
+            "b1".parse::<Phone>(),
+            Err(ParseError::UnknownPhone("b1".to_string()))
+        );
+        assert_eq!(
+            "xx".parse::<Phone>(),
+            Err(ParseError::UnknownPhone("xx".to_string()))
+        );
+    }
+
+    #[test]
+    fn folding() {
+        let all = phones(TIMIT);
+        let classes = |fold: fn(&Phone) -> Option<&'static str>| {
+            all.iter().filter_map(fold).collect::<HashSet<_>>().len()
+        };
+        assert_eq!(classes(Phone::fold_48), 48);
+        assert_eq!(classes(Phone::fold_39), 39);
+        assert_eq!(Phone::KCL.fold_48(), Some("cl"));
+        assert_eq!(Phone::KCL.fold_39(), Some("sil"));
+        assert_eq!(Phone::ZH.fold_48(), Some("zh"));
+        assert_eq!(Phone::ZH.fold_39(), Some("sh"));
+        assert_eq!(Phone::Q.fold_39(), None);
+    }
+
+    #[test]
+    fn converting() {
+        for phone in phones(TIMIT) {
+            let symbols = phone.to_symbols();
+            if phone.is_silence() || phone == Phone::Q {
+                assert!(symbols.is_empty());
+            }
+            for symbol in symbols {
+                if Phone::from(symbol) == phone {
+                    assert_eq!(phone.to_symbols(), vec![symbol]);
+                }
+            }
+        }
+
+        // an unreleased closure still counts as its stop
+        let rule: ExtendedRule = "bottle /bcl b aa1 tcl el/".parse().unwrap();
+        assert_eq!(rule.label(), "bottle");
+        assert_eq!(rule.pronunciation().len(), 5);
+        assert_eq!(
+            rule.to_rule().pronunciation(),
+            &[
+                Symbol::B,
+                Symbol::AA(Stress::Primary),
+                Symbol::T,
+                Symbol::AH(Stress::None),
+                Symbol::L
+            ]
+        );
+
+        // affricates are written with the closure of their stop
+        let church = collapse(&phones("h# tcl ch er1 tcl ch h#"));
+        assert_eq!(church, vec![Symbol::CH, Symbol::ER(Stress::Primary), Symbol::CH]);
+        let judge = collapse(&phones("dcl jh ah1 dcl jh"));
+        assert_eq!(judge, vec![Symbol::JH, Symbol::AH(Stress::Primary), Symbol::JH]);
+    }
+}
@@ -0,0 +1,290 @@
+//! Choosing between the pronunciations of words that have more than one
+//!
+//! The dictionary lists the variants of a word in no particular order, so the first one isn't
+//! always the one that's wanted. A `Policy` ranks them, either by their shape or by weights
+//! that can be kept in a file with one weighted pronunciation per line, like the `lexiconp.txt`
+//! files of speech recognisers.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::core::{Rule, Stress, Symbol};
+use crate::errors::{Error, Result};
+use crate::Cmudict;
+
+/// How to choose between the pronunciations of a word
+///
+/// Variants that a policy can't tell apart are left in the order of the dictionary.
+///
+/// # Example
+///
+/// ```
+/// use cmudict_fast as cmudict;
+/// use cmudict::{Cmudict, Policy, Stress, Symbol, Weights};
+/// # use cmudict::Result;
+/// #
+/// # fn main() {
+/// #   if let Err(_) = run() {
+/// #     panic!("error!");
+/// #   }
+/// # }
+/// # fn run() -> Result<()> {
+///
+/// let dict = Cmudict::new("./resources/cmudict.dict")?;
+/// let tomato = |policy: &Policy| dict.select("tomato", policy).unwrap().pronunciation()[3];
+/// assert_eq!(tomato(&Policy::First), Symbol::EY(Stress::Primary));
+/// let merged = Policy::Prefer(vec![Symbol::AA(Stress::None)]);
+/// assert_eq!(tomato(&merged), Symbol::AA(Stress::Primary));
+///
+/// let weights: Weights = "
+/// tomato 0.2 T AH0 M EY1 T OW2
+/// tomato 0.8 T AH0 M AA1 T OW2
+/// ".parse()?;
+/// assert_eq!(tomato(&Policy::Weighted(&weights)), Symbol::AA(Stress::Primary));
+///
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Policy<'a> {
+    /// The first pronunciation, in the order of the dictionary
+    #[default]
+    First,
+    /// The pronunciation with the fewest symbols
+    Shortest,
+    /// The pronunciation with the most reduced vowels, `AH0`, `IH0` and `ER0`, as the words
+    /// are said in running speech
+    MostReduced,
+    /// The pronunciation with the most of these symbols, whatever their stress, for
+    /// preferring the vowels of a dialect, like `AA` over `AO` where "cot" and "caught" sound
+    /// the same
+    Prefer(Vec<Symbol>),
+    /// The pronunciation with the highest weight, with those that have none last
+    Weighted(&'a Weights),
+}
+
+impl<'a> Policy<'a> {
+    /// Ranks the pronunciations of a word, the one this policy would choose first
+    pub fn rank<'r>(&self, word: &str, rules: &'r [Rule]) -> Vec<&'r Rule> {
+        let word = word.to_lowercase();
+        let score = |rule: &Rule| -> f64 {
+            let phones = rule.pronunciation();
+            match self {
+                Policy::First => 0.0,
+                Policy::Shortest => -(phones.len() as f64),
+                Policy::MostReduced => phones.iter().filter(|s| is_reduced(s)).count() as f64,
+                Policy::Prefer(preferred) => {
+                    let preferred: Vec<Symbol> = preferred.iter().map(unstressed).collect();
+                    phones.iter().filter(|s| preferred.contains(&unstressed(s))).count() as f64
+                }
+                Policy::Weighted(weights) => {
+                    weights.weight(&word, phones).unwrap_or(f64::NEG_INFINITY)
+                }
+            }
+        };
+        let mut ranked: Vec<(f64, &Rule)> = rules.iter().map(|rule| (score(rule), rule)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.into_iter().map(|(_, rule)| rule).collect()
+    }
+
+    /// Chooses one of the pronunciations of a word, or `None` if there are none
+    pub fn select<'r>(&self, word: &str, rules: &'r [Rule]) -> Option<&'r Rule> {
+        self.rank(word, rules).into_iter().next()
+    }
+}
+
+fn unstressed(symbol: &Symbol) -> Symbol {
+    symbol.with_stress(Stress::None)
+}
+
+fn is_reduced(symbol: &Symbol) -> bool {
+    matches!(
+        symbol,
+        Symbol::AH(Stress::None) | Symbol::IH(Stress::None) | Symbol::ER(Stress::None)
+    )
+}
+
+/// Weights for the pronunciations of words, like how often each one is heard
+///
+/// Weights can be read from and written to text with one pronunciation per line: the word,
+/// its weight, and the symbols of the pronunciation. A variant marker on the word, like the
+/// `(2)` of `tomato(2)`, is ignored. Blank lines and lines starting with `;;` are skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Weights {
+    weights: HashMap<String, Vec<(Vec<Symbol>, f64)>>,
+}
+
+impl Weights {
+    /// Creates an empty set of weights
+    pub fn new() -> Weights {
+        Weights::default()
+    }
+
+    /// Loads weights from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Weights> {
+        let file = File::open(path)?;
+        Weights::read(BufReader::new(file))
+    }
+
+    /// Saves the weights to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Writes the weights in their text format, with the words in alphabetical order
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let mut words: Vec<&String> = self.weights.keys().collect();
+        words.sort();
+        for word in words {
+            for (phones, weight) in &self.weights[word] {
+                write!(writer, "{} {}", word, weight)?;
+                for phone in phones {
+                    write!(writer, " {}", phone)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read<R: BufRead>(reader: R) -> Result<Weights> {
+        let mut weights = Weights::new();
+        for line in crate::entry_lines(reader) {
+            let (idx, line) = line?;
+            let mut parts = line.split_whitespace();
+            let word = parts.next().ok_or(Error::InvalidLine(idx))?;
+            let weight = parts
+                .next()
+                .and_then(|w| w.parse::<f64>().ok())
+                .ok_or(Error::InvalidLine(idx))?;
+            let phones = parts
+                .map(Symbol::from_str)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            if phones.is_empty() {
+                return Err(Error::InvalidLine(idx));
+            }
+            weights.insert(crate::split_label(word), phones, weight);
+        }
+        Ok(weights)
+    }
+
+    /// Sets the weight of a pronunciation of a word, whatever the word's case
+    pub fn insert(&mut self, word: &str, pronunciation: Vec<Symbol>, weight: f64) {
+        let variants = self.weights.entry(word.to_lowercase()).or_default();
+        match variants.iter_mut().find(|(phones, _)| *phones == pronunciation) {
+            Some(variant) => variant.1 = weight,
+            None => variants.push((pronunciation, weight)),
+        }
+    }
+
+    /// Returns the weight of a pronunciation of a word, if it has one
+    pub fn weight(&self, word: &str, pronunciation: &[Symbol]) -> Option<f64> {
+        self.weights
+            .get(&word.to_lowercase())?
+            .iter()
+            .find(|(phones, _)| phones == pronunciation)
+            .map(|&(_, weight)| weight)
+    }
+
+    /// Returns the weight of a pronunciation of a word divided by the weights of all of the
+    /// word's pronunciations, which turns counts of how often each one was heard into
+    /// probabilities
+    pub fn probability(&self, word: &str, pronunciation: &[Symbol]) -> Option<f64> {
+        let weight = self.weight(word, pronunciation)?;
+        let total: f64 = self.weights[&word.to_lowercase()].iter().map(|(_, w)| w).sum();
+        Some(if total > 0.0 { weight / total } else { 0.0 })
+    }
+
+    /// Returns the number of words with weighted pronunciations
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Returns `true` if no pronunciations have weights
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
+
+impl FromStr for Weights {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Weights> {
+        Weights::read(Cursor::new(s))
+    }
+}
+
+impl Cmudict {
+    /// Looks up a word and chooses one of its pronunciations with `policy`
+    pub fn select(&self, word: &str, policy: &Policy) -> Option<&Rule> {
+        policy.select(word, self.get(word)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        let d = Cmudict::new("./resources/cmudict.dict").expect("Could not create Cmudict");
+        let every = d.get("every").unwrap();
+        assert_eq!(Policy::First.select("every", every), Some(&every[0]));
+        assert_eq!(Policy::MostReduced.select("every", every), Some(&every[0]));
+        let everywhere = d.get("everywhere").unwrap();
+        assert_eq!(d.select("everywhere", &Policy::Shortest), Some(&everywhere[0]));
+
+        let route = d.get("routes").unwrap();
+        let prefer = Policy::Prefer(vec![Symbol::UH(Stress::Primary)]);
+        let ranked = prefer.rank("routes", route);
+        assert_eq!(ranked, vec![&route[2], &route[0], &route[1]]);
+
+        let mut weights = Weights::new();
+        weights.insert("Routes", route[1].pronunciation().to_vec(), 3.0);
+        weights.insert("routes", route[0].pronunciation().to_vec(), 1.0);
+        let ranked = Policy::Weighted(&weights).rank("routes", route);
+        assert_eq!(ranked, vec![&route[1], &route[0], &route[2]]);
+
+        assert_eq!(Policy::Shortest.select("nothing", &[]), None);
+        assert_eq!(d.select("zzzzing", &Policy::First), None);
+    }
+
+    #[test]
+    fn weights() {
+        use Symbol::*;
+        let text = "
+;; counts
+either(2) 1 AY1 DH ER0
+either 3 IY1 DH ER0
+";
+        let mut weights: Weights = text.parse().unwrap();
+        let either = [IY(Stress::Primary), DH, ER(Stress::None)];
+        assert_eq!(weights.len(), 1);
+        assert_eq!(weights.weight("EITHER", &either), Some(3.0));
+        assert_eq!(weights.probability("either", &either), Some(0.75));
+        assert_eq!(weights.weight("either", &[IY(Stress::Primary)]), None);
+        assert_eq!(weights.probability("neither", &either), None);
+
+        weights.insert("either", either.to_vec(), 1.0);
+        weights.insert("a", vec![AH(Stress::None)], 0.5);
+        let mut written = Vec::new();
+        weights.write(&mut written).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert_eq!(written, "a 0.5 AH0\neither 1 AY1 DH ER0\neither 1 IY1 DH ER0\n");
+        assert_eq!(written.parse::<Weights>().unwrap(), weights);
+
+        assert!("either IY1 DH ER0".parse::<Weights>().is_err());
+        assert!("either 0.5".parse::<Weights>().is_err());
+        assert!("either 0.5 XX".parse::<Weights>().is_err());
+    }
+}